@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::{
+    config::GameConfiguration,
+    game::{MathAnswer, MathQuestion},
+};
+
+pub const CHECKPOINT_PATH: &str = "checkpoint.json";
+// how often a game in progress is written to disk
+pub const CHECKPOINT_INTERVAL_MS: i64 = 5000;
+
+// a game in progress, saved so it can be picked up again after a quit or crash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub saved_at: DateTime<Utc>,
+    pub config: GameConfiguration,
+    pub seed: u64,
+    pub score: i32,
+    pub lives: i32,
+    pub streak: i32,
+    pub best_streak: i32,
+    pub combo_score: i32,
+    // game clock and time spent paused when the checkpoint was taken
+    pub elapsed_ms: i64,
+    pub paused_ms: i64,
+    // time already spent on the current question
+    pub question_ms: i64,
+    pub current_question: MathQuestion,
+    pub answers: Vec<MathAnswer>,
+}
+
+impl Checkpoint {
+    // Load the checkpoint left by an unfinished game, None if there is none or it is unreadable
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<Checkpoint> {
+        let mut contents = String::new();
+        File::open(path).ok()?.read_to_string(&mut contents).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    // Written to a temporary file and renamed over the checkpoint, so a crash part way
+    // through a save never leaves a truncated checkpoint behind
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        let temp = path.as_ref().with_extension("json.tmp");
+        let mut file = File::create(&temp)?;
+        file.write_all(json.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(temp, path)
+    }
+
+    // Remove the checkpoint once its game is over, a missing file is fine
+    pub fn remove<P: AsRef<Path>>(path: P) {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,7 +1,140 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+use crate::{keymap::Action, util::Sign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    // score as many as possible before the timer runs out
+    #[default]
+    Timed,
+    // play until all lives are lost to wrong answers or question timeouts
+    Survival,
+    // answer a fixed number of questions as fast as possible, the final time is the score
+    Sprint,
+    // numbers are flashed one at a time and their sum is asked for
+    FlashSum,
+    // recall a briefly shown number, its length adapts to how well it goes
+    DigitSpan,
+    // quote markets on the sum of hidden dice, scored on profit
+    Market,
+    // timed multiplication drawn from a times table, favouring facts not yet mastered
+    Mastery,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 7] = [
+        GameMode::Timed,
+        GameMode::Survival,
+        GameMode::Sprint,
+        GameMode::FlashSum,
+        GameMode::DigitSpan,
+        GameMode::Market,
+        GameMode::Mastery,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Timed => "Timed",
+            GameMode::Survival => "Survival",
+            GameMode::Sprint => "Sprint",
+            GameMode::FlashSum => "Flash Sum",
+            GameMode::DigitSpan => "Digit Span",
+            GameMode::Market => "Market Making",
+            GameMode::Mastery => "Times Tables",
+        }
+    }
+}
+
+// how division questions are answered, anything but exact leaves a remainder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DivisionFormat {
+    #[default]
+    Exact,
+    // quotient and remainder, like 3 r 2
+    Remainder,
+    // rounded to this many decimal places
+    Decimal(u32),
+    // like 3 2/5
+    MixedFraction,
+}
+
+impl DivisionFormat {
+    pub const ALL: [DivisionFormat; 4] = [
+        DivisionFormat::Exact,
+        DivisionFormat::Remainder,
+        DivisionFormat::Decimal(2),
+        DivisionFormat::MixedFraction,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DivisionFormat::Exact => "Exact",
+            DivisionFormat::Remainder => "Remainder",
+            DivisionFormat::Decimal(_) => "Decimal",
+            DivisionFormat::MixedFraction => "Mixed fraction",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameConfiguration {
     pub endless: bool,
+    pub mode: GameMode,
     pub timer: i32,
+    pub lives: i32,
+    pub survival_question_limit: i32,
+    // optional deadline for every question, in seconds
+    pub question_limit: Option<i32>,
+    // how long the answer to a timed out question stays on screen
+    pub timeout_flash_ms: i64,
+    // seconds added to the game clock for each skipped question
+    pub skip_penalty_s: i32,
+    pub sprint_questions: i32,
+    // pick from options 1-5 instead of typing answers
+    pub multiple_choice: bool,
+    // marks lost for each wrong multiple choice pick
+    pub wrong_penalty: i32,
+    // each question starts from the previous answer
+    pub chained: bool,
+    pub division_format: DivisionFormat,
+    // order of magnitude estimates instead of exact answers
+    pub estimation: bool,
+    // estimates within this percentage of the answer count as correct
+    pub estimate_tolerance_pct: i32,
+    // fast consecutive answers build a score multiplier
+    pub combo_scoring: bool,
+    // answers slower than this break the streak
+    pub combo_threshold_ms: i64,
+    // operations questions are drawn from
+    pub signs: Vec<Sign>,
+    // interval training plan, reps x work seconds / rest seconds
+    pub interval_plan: String,
+    // numbers flashed per flash-sum round and the digits in each
+    pub flash_count: i32,
+    pub flash_digits: i32,
+    // how long each flashed number stays on screen
+    pub flash_ms: i64,
+    pub flash_rounds: i32,
+    // digit span numbers are typed back in reverse order
+    pub span_reversed: bool,
+    // digits in the first digit span number
+    pub span_start: i32,
+    // display time per digit of the digit span number
+    pub span_digit_ms: i64,
+    pub span_trials: i32,
+    // times table covered by the mastery mode, both bounds inclusive
+    pub table_lower: i32,
+    pub table_upper: i32,
+    // a fact is mastered once its recent answers are all correct and this fast on average
+    pub mastery_ms: i64,
+    // hidden dice in a market making game, one round is quoted per die
+    pub market_dice: i32,
+    // widest bid/ask spread the player may quote
+    pub market_max_width: i32,
+    // actions that ask for confirmation while a game is being played
+    pub confirm: Vec<Action>,
+    // keep games abandoned by restarting, returning to start or quitting in the history
+    pub save_abandoned: bool,
     pub qr: QuestionRanges,
     pub debug: bool,
     pub debug_questions: i32,
@@ -11,14 +144,46 @@ impl Default for GameConfiguration {
     fn default() -> Self {
         Self {
             endless: false,
+            mode: GameMode::Timed,
             timer: 120,
+            lives: 3,
+            survival_question_limit: 10,
+            question_limit: None,
+            timeout_flash_ms: 1500,
+            skip_penalty_s: 0,
+            sprint_questions: 50,
+            multiple_choice: false,
+            wrong_penalty: 1,
+            chained: false,
+            division_format: DivisionFormat::Exact,
+            estimation: false,
+            estimate_tolerance_pct: 10,
+            combo_scoring: false,
+            combo_threshold_ms: 3000,
+            signs: Sign::ARITHMETIC.to_vec(),
+            interval_plan: "6x45/15".to_string(),
+            flash_count: 5,
+            flash_digits: 2,
+            flash_ms: 800,
+            flash_rounds: 5,
+            span_reversed: false,
+            span_start: 4,
+            span_digit_ms: 500,
+            span_trials: 12,
+            table_lower: 2,
+            table_upper: 19,
+            mastery_ms: 3000,
+            market_dice: 5,
+            market_max_width: 4,
+            confirm: Action::CONFIRMABLE.to_vec(),
+            save_abandoned: false,
             qr: QuestionRanges::default(),
             debug: false,
             debug_questions: 72,
         }
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuestionRanges {
     pub add_lower: i32,
     pub add_upper: i32,
@@ -26,6 +191,8 @@ pub struct QuestionRanges {
     pub mult_rhs_upper: i32,
     pub mult_lhs_lower: i32,
     pub mult_lhs_upper: i32,
+    // largest running total allowed in chained mode
+    pub chain_upper: i32,
 }
 
 impl Default for QuestionRanges {
@@ -37,6 +204,380 @@ impl Default for QuestionRanges {
             mult_lhs_upper: 12,
             mult_rhs_lower: 2,
             mult_rhs_upper: 100,
+            chain_upper: 999,
+        }
+    }
+}
+
+// the part of the config that decides which questions are asked, kept with each game
+// so racing its ghost asks the same questions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuestionSettings {
+    pub signs: Vec<Sign>,
+    pub qr: QuestionRanges,
+    pub multiple_choice: bool,
+    pub chained: bool,
+    pub estimation: bool,
+    pub division_format: DivisionFormat,
+}
+
+impl QuestionSettings {
+    pub fn from_config(config: &GameConfiguration) -> QuestionSettings {
+        QuestionSettings {
+            signs: config.signs.clone(),
+            qr: config.qr.clone(),
+            multiple_choice: config.multiple_choice,
+            chained: config.chained,
+            estimation: config.estimation,
+            division_format: config.division_format,
+        }
+    }
+
+    pub fn apply(&self, config: &mut GameConfiguration) {
+        config.signs = self.signs.clone();
+        config.qr = self.qr.clone();
+        config.multiple_choice = self.multiple_choice;
+        config.chained = self.chained;
+        config.estimation = self.estimation;
+        config.division_format = self.division_format;
+    }
+}
+
+// the part of the config that decides when a game is over, kept with each game
+// so its ghost is raced to the same finish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndSettings {
+    pub endless: bool,
+    pub timer: i32,
+    pub lives: i32,
+    pub survival_question_limit: i32,
+    pub question_limit: Option<i32>,
+    pub timeout_flash_ms: i64,
+    pub skip_penalty_s: i32,
+    pub sprint_questions: i32,
+    pub table_lower: i32,
+    pub table_upper: i32,
+    pub mastery_ms: i64,
+}
+
+impl EndSettings {
+    pub fn from_config(config: &GameConfiguration) -> EndSettings {
+        EndSettings {
+            endless: config.endless,
+            timer: config.timer,
+            lives: config.lives,
+            survival_question_limit: config.survival_question_limit,
+            question_limit: config.question_limit,
+            timeout_flash_ms: config.timeout_flash_ms,
+            skip_penalty_s: config.skip_penalty_s,
+            sprint_questions: config.sprint_questions,
+            table_lower: config.table_lower,
+            table_upper: config.table_upper,
+            mastery_ms: config.mastery_ms,
+        }
+    }
+
+    pub fn apply(&self, config: &mut GameConfiguration) {
+        config.endless = self.endless;
+        config.timer = self.timer;
+        config.lives = self.lives;
+        config.survival_question_limit = self.survival_question_limit;
+        config.question_limit = self.question_limit;
+        config.timeout_flash_ms = self.timeout_flash_ms;
+        config.skip_penalty_s = self.skip_penalty_s;
+        config.sprint_questions = self.sprint_questions;
+        config.table_lower = self.table_lower;
+        config.table_upper = self.table_upper;
+        config.mastery_ms = self.mastery_ms;
+    }
+}
+
+// settings that can be changed from the settings screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Setting {
+    Mode,
+    Timer,
+    Lives,
+    SurvivalQuestionLimit,
+    QuestionLimit,
+    SkipPenalty,
+    SprintQuestions,
+    MultipleChoice,
+    WrongPenalty,
+    Chained,
+    DivisionFormat,
+    DecimalPlaces,
+    Estimation,
+    EstimateTolerance,
+    ComboScoring,
+    ComboThreshold,
+    Operation(Sign),
+    IntervalPlan,
+    FlashCount,
+    FlashDigits,
+    FlashSpeed,
+    FlashRounds,
+    SpanReversed,
+    SpanStart,
+    SpanSpeed,
+    SpanTrials,
+    TableLower,
+    TableUpper,
+    MasteryTime,
+    MarketDice,
+    MarketMaxWidth,
+    Confirm(Action),
+    SaveAbandoned,
+}
+
+// plans offered on the settings screen, others can be set in the default config
+const INTERVAL_PLANS: [&str; 4] = ["6x45/15", "4x60/30", "8x20/10", "3x120/60"];
+
+impl Setting {
+    pub const ALL: [Setting; 44] = [
+        Setting::Mode,
+        Setting::Timer,
+        Setting::Lives,
+        Setting::SurvivalQuestionLimit,
+        Setting::QuestionLimit,
+        Setting::SkipPenalty,
+        Setting::SprintQuestions,
+        Setting::MultipleChoice,
+        Setting::WrongPenalty,
+        Setting::Chained,
+        Setting::DivisionFormat,
+        Setting::DecimalPlaces,
+        Setting::Estimation,
+        Setting::EstimateTolerance,
+        Setting::ComboScoring,
+        Setting::ComboThreshold,
+        Setting::Operation(Sign::Add),
+        Setting::Operation(Sign::Subtract),
+        Setting::Operation(Sign::Multiply),
+        Setting::Operation(Sign::Divide),
+        Setting::Operation(Sign::Prime),
+        Setting::Operation(Sign::Factorise),
+        Setting::Operation(Sign::Gcd),
+        Setting::Operation(Sign::Lcm),
+        Setting::Operation(Sign::Mod),
+        Setting::IntervalPlan,
+        Setting::FlashCount,
+        Setting::FlashDigits,
+        Setting::FlashSpeed,
+        Setting::FlashRounds,
+        Setting::SpanReversed,
+        Setting::SpanStart,
+        Setting::SpanSpeed,
+        Setting::SpanTrials,
+        Setting::TableLower,
+        Setting::TableUpper,
+        Setting::MasteryTime,
+        Setting::MarketDice,
+        Setting::MarketMaxWidth,
+        Setting::Confirm(Action::Restart),
+        Setting::Confirm(Action::EndGame),
+        Setting::Confirm(Action::ReturnToStart),
+        Setting::Confirm(Action::Quit),
+        Setting::SaveAbandoned,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Mode => "Mode",
+            Setting::Timer => "Timer (s)",
+            Setting::Lives => "Lives",
+            Setting::SurvivalQuestionLimit => "Survival question limit (s)",
+            Setting::QuestionLimit => "Question limit (s)",
+            Setting::SkipPenalty => "Skip penalty (s)",
+            Setting::SprintQuestions => "Sprint questions",
+            Setting::MultipleChoice => "Multiple choice",
+            Setting::WrongPenalty => "Multiple choice wrong penalty",
+            Setting::Chained => "Chained answers",
+            Setting::DivisionFormat => "Division answers",
+            Setting::DecimalPlaces => "Division decimal places",
+            Setting::Estimation => "Estimation (e notation)",
+            Setting::EstimateTolerance => "Estimate tolerance (%)",
+            Setting::ComboScoring => "Combo scoring",
+            Setting::ComboThreshold => "Combo threshold (ms)",
+            Setting::Operation(sign) => sign.name(),
+            Setting::IntervalPlan => "Interval plan (reps x on/off s)",
+            Setting::FlashCount => "Flash sum numbers",
+            Setting::FlashDigits => "Flash sum digits",
+            Setting::FlashSpeed => "Flash sum speed (ms)",
+            Setting::FlashRounds => "Flash sum rounds",
+            Setting::SpanReversed => "Digit span reversed",
+            Setting::SpanStart => "Digit span start length",
+            Setting::SpanSpeed => "Digit span time per digit (ms)",
+            Setting::SpanTrials => "Digit span trials",
+            Setting::TableLower => "Times table from",
+            Setting::TableUpper => "Times table to",
+            Setting::MasteryTime => "Mastery time (ms)",
+            Setting::MarketDice => "Market making dice",
+            Setting::MarketMaxWidth => "Market making max width",
+            Setting::Confirm(action) => match action {
+                Action::Restart => "Confirm reset",
+                Action::EndGame => "Confirm end",
+                Action::ReturnToStart => "Confirm return to start",
+                _ => "Confirm quit",
+            },
+            Setting::SaveAbandoned => "Abandoned games",
+        }
+    }
+
+    pub fn value(&self, config: &GameConfiguration) -> String {
+        match self {
+            Setting::Mode => config.mode.name().to_string(),
+            Setting::Timer => config.timer.to_string(),
+            Setting::Lives => config.lives.to_string(),
+            Setting::SurvivalQuestionLimit => config.survival_question_limit.to_string(),
+            Setting::QuestionLimit => config
+                .question_limit
+                .map_or("Off".to_string(), |l| l.to_string()),
+            Setting::SkipPenalty => config.skip_penalty_s.to_string(),
+            Setting::SprintQuestions => config.sprint_questions.to_string(),
+            Setting::MultipleChoice => on_off(config.multiple_choice),
+            Setting::WrongPenalty => config.wrong_penalty.to_string(),
+            Setting::Chained => on_off(config.chained),
+            Setting::DivisionFormat => config.division_format.name().to_string(),
+            Setting::DecimalPlaces => match config.division_format {
+                DivisionFormat::Decimal(places) => places.to_string(),
+                _ => "-".to_string(),
+            },
+            Setting::Estimation => on_off(config.estimation),
+            Setting::EstimateTolerance => config.estimate_tolerance_pct.to_string(),
+            Setting::ComboScoring => on_off(config.combo_scoring),
+            Setting::ComboThreshold => config.combo_threshold_ms.to_string(),
+            Setting::Operation(sign) => on_off(config.signs.contains(sign)),
+            Setting::IntervalPlan => config.interval_plan.clone(),
+            Setting::FlashCount => config.flash_count.to_string(),
+            Setting::FlashDigits => config.flash_digits.to_string(),
+            Setting::FlashSpeed => config.flash_ms.to_string(),
+            Setting::FlashRounds => config.flash_rounds.to_string(),
+            Setting::SpanReversed => on_off(config.span_reversed),
+            Setting::SpanStart => config.span_start.to_string(),
+            Setting::SpanSpeed => config.span_digit_ms.to_string(),
+            Setting::SpanTrials => config.span_trials.to_string(),
+            Setting::TableLower => config.table_lower.to_string(),
+            Setting::TableUpper => config.table_upper.to_string(),
+            Setting::MasteryTime => config.mastery_ms.to_string(),
+            Setting::MarketDice => config.market_dice.to_string(),
+            Setting::MarketMaxWidth => config.market_max_width.to_string(),
+            Setting::Confirm(action) => on_off(config.confirm.contains(action)),
+            Setting::SaveAbandoned => match config.save_abandoned {
+                true => "Save".to_string(),
+                false => "Discard".to_string(),
+            },
         }
     }
+
+    // step the setting forwards or backwards, delta is +1 or -1
+    pub fn adjust(&self, config: &mut GameConfiguration, delta: i32) {
+        match self {
+            Setting::Mode => {
+                let i = GameMode::ALL
+                    .iter()
+                    .position(|m| *m == config.mode)
+                    .unwrap_or_default() as i32;
+                let len = GameMode::ALL.len() as i32;
+                config.mode = GameMode::ALL[(i + delta).rem_euclid(len) as usize];
+            }
+            Setting::Timer => config.timer = (config.timer + delta * 10).max(10),
+            Setting::Lives => config.lives = (config.lives + delta).max(1),
+            Setting::SurvivalQuestionLimit => {
+                config.survival_question_limit = (config.survival_question_limit + delta).max(1)
+            }
+            // stepping below one second turns the limit off
+            Setting::QuestionLimit => {
+                let limit = config.question_limit.unwrap_or(0) + delta;
+                config.question_limit = (limit > 0).then_some(limit);
+            }
+            Setting::SkipPenalty => config.skip_penalty_s = (config.skip_penalty_s + delta).max(0),
+            Setting::SprintQuestions => {
+                config.sprint_questions = (config.sprint_questions + delta * 5).max(5)
+            }
+            Setting::MultipleChoice => config.multiple_choice = !config.multiple_choice,
+            Setting::WrongPenalty => config.wrong_penalty = (config.wrong_penalty + delta).max(0),
+            Setting::Chained => config.chained = !config.chained,
+            Setting::DivisionFormat => {
+                let i = DivisionFormat::ALL
+                    .iter()
+                    .position(|f| f.name() == config.division_format.name())
+                    .unwrap_or_default() as i32;
+                let len = DivisionFormat::ALL.len() as i32;
+                config.division_format = DivisionFormat::ALL[(i + delta).rem_euclid(len) as usize];
+            }
+            // only applies while answering as decimals
+            Setting::DecimalPlaces => {
+                if let DivisionFormat::Decimal(places) = &mut config.division_format {
+                    *places = (*places as i32 + delta).clamp(1, 4) as u32;
+                }
+            }
+            Setting::Estimation => config.estimation = !config.estimation,
+            Setting::EstimateTolerance => {
+                config.estimate_tolerance_pct = (config.estimate_tolerance_pct + delta * 5).max(5)
+            }
+            Setting::ComboScoring => config.combo_scoring = !config.combo_scoring,
+            Setting::ComboThreshold => {
+                config.combo_threshold_ms = (config.combo_threshold_ms + delta as i64 * 250).max(250)
+            }
+            // at least one operation always stays enabled
+            Setting::Operation(sign) => {
+                if !config.signs.contains(sign) {
+                    config.signs.push(*sign);
+                    config.signs.sort_by_key(|s| Sign::ALL.iter().position(|a| a == s));
+                } else if config.signs.len() > 1 {
+                    config.signs.retain(|s| s != sign);
+                }
+            }
+            Setting::IntervalPlan => {
+                let i = INTERVAL_PLANS
+                    .iter()
+                    .position(|p| *p == config.interval_plan)
+                    .map_or(0, |i| i as i32 + delta);
+                config.interval_plan =
+                    INTERVAL_PLANS[i.rem_euclid(INTERVAL_PLANS.len() as i32) as usize].to_string();
+            }
+            Setting::FlashCount => config.flash_count = (config.flash_count + delta).max(2),
+            Setting::FlashDigits => config.flash_digits = (config.flash_digits + delta).clamp(1, 6),
+            Setting::FlashSpeed => {
+                config.flash_ms = (config.flash_ms + delta as i64 * 100).max(100)
+            }
+            Setting::FlashRounds => config.flash_rounds = (config.flash_rounds + delta).max(1),
+            Setting::SpanReversed => config.span_reversed = !config.span_reversed,
+            Setting::SpanStart => config.span_start = (config.span_start + delta).clamp(2, 20),
+            Setting::SpanSpeed => {
+                config.span_digit_ms = (config.span_digit_ms + delta as i64 * 100).max(100)
+            }
+            Setting::SpanTrials => config.span_trials = (config.span_trials + delta).max(1),
+            Setting::TableLower => {
+                config.table_lower = (config.table_lower + delta).clamp(1, config.table_upper)
+            }
+            Setting::TableUpper => {
+                config.table_upper = (config.table_upper + delta).clamp(config.table_lower, 99)
+            }
+            Setting::MasteryTime => {
+                config.mastery_ms = (config.mastery_ms + delta as i64 * 250).max(250)
+            }
+            Setting::MarketDice => config.market_dice = (config.market_dice + delta).clamp(2, 10),
+            Setting::MarketMaxWidth => {
+                config.market_max_width = (config.market_max_width + delta).max(1)
+            }
+            Setting::Confirm(action) => {
+                if config.confirm.contains(action) {
+                    config.confirm.retain(|a| a != action);
+                } else {
+                    config.confirm.push(*action);
+                }
+            }
+            Setting::SaveAbandoned => config.save_abandoned = !config.save_abandoned,
+        }
+    }
+}
+
+fn on_off(value: bool) -> String {
+    if value {
+        "On".to_string()
+    } else {
+        "Off".to_string()
+    }
 }
@@ -0,0 +1,38 @@
+use crate::{game::MathGame, keymap::Action};
+
+// a destructive action waiting on the player to confirm it
+#[derive(Debug, Clone, Copy)]
+pub struct Confirm {
+    pub action: Action,
+    // the game clocks were held to ask, so are released once it is answered
+    pub held: bool,
+}
+
+impl Confirm {
+    pub fn question(&self, game: &MathGame) -> String {
+        let abandon = match game.gameconfig.save_abandoned {
+            true => "the game will be saved as abandoned",
+            false => "the game will not be saved",
+        };
+        match self.action {
+            Action::Restart => format!("Restart? {}", abandon),
+            Action::EndGame => "End the game now and save it?".to_string(),
+            Action::ReturnToStart => format!("Return to start? {}", abandon),
+            // plain games are checkpointed on quitting rather than abandoned
+            Action::Quit if game.to_checkpoint().is_some() => {
+                "Quit? the game can be resumed next time".to_string()
+            }
+            Action::Quit => format!("Quit? {}", abandon),
+            Action::Resume => match &game.checkpoint {
+                Some(checkpoint) => format!(
+                    "Resume the unfinished {} game? score {} after {}s",
+                    checkpoint.config.mode.name(),
+                    checkpoint.score,
+                    checkpoint.elapsed_ms / 1000
+                ),
+                None => "Resume the unfinished game?".to_string(),
+            },
+            action => format!("{}?", action.label()),
+        }
+    }
+}
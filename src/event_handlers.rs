@@ -1,70 +1,268 @@
 use std::io;
 
-use chrono::Local;
-use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    layout::Position,
+};
 
-use crate::game::{GameState, MathGame, MathQuestion};
+use crate::{
+    game::{AnswerOutcome, GameState, MathGame},
+    input::InputKind,
+    keymap::{Action, Screen},
+    mouse,
+    renderers::table,
+    session::Session,
+    util::{self, Sign},
+};
 
 //handle game tick, used to check if timeout has occured
 pub fn handle_tick_event(game: &mut MathGame) {
-    if game.get_elapsed_time_seconds() > game.gameconfig.timer
-        && game.gamestate == GameState::Inprogress
-    {
+    game.handle_network_messages();
+    game.update_multiplayer();
+    if game.confirm.is_some() {
+        return;
+    }
+    game.update_checkpoint();
+    game.update_rest(false);
+    if game.gamestate != GameState::Inprogress || game.paused_at.is_some() {
+        return;
+    }
+    game.update_flash();
+    if let Some(limit) = game.question_limit() {
+        if game.flash.is_none() && game.current_question.elapsed_seconds() >= limit as i64 {
+            game.handle_question_timeout();
+        }
+    }
+    if game.is_game_over() {
         game.handle_game_end(true);
     }
 }
 
 //top level event handler, subordinates to other handlers depending on game state
 pub fn handle_events(key_event: KeyEvent, game: &mut MathGame) -> io::Result<()> {
+    // a confirmation takes every key until it is answered
+    if game.confirm.is_some() {
+        handle_key_event_confirm(game, key_event);
+        return Ok(());
+    }
     match game.gamestate {
         GameState::Setup => handle_key_event_splash(game, key_event),
+        GameState::Lobby => handle_key_event_lobby(game, key_event),
         GameState::Inprogress => handle_key_event_game(game, key_event),
-        GameState::EndingSplash => handle_end_event_splash(game, key_event),
+        GameState::FlashSum | GameState::DigitSpan | GameState::Market => {
+            handle_key_event_drill(game, key_event)
+        }
+        // flash-sum, digit span and market results share the end screen keys
+        GameState::EndingSplash
+        | GameState::FlashResults
+        | GameState::SpanResults
+        | GameState::MarketResults => handle_end_event_splash(game, key_event),
+        GameState::VersusSplash | GameState::SessionSplash => {
+            handle_key_event_versus(game, key_event)
+        }
+        GameState::Resting => handle_key_event_resting(game, key_event),
         GameState::HistorySplash => handle_key_event_history(game, key_event),
-        GameState::SettingsSpash => handle_key_event_game(game, key_event),
+        GameState::SettingsSpash => handle_key_event_settings(game, key_event),
     }
     Ok(())
 }
 
-fn handle_end_event_splash(game: &mut MathGame, key_event: KeyEvent) {
+// clicks pick splash options, table rows and question bars, the wheel scrolls the table under it
+pub fn handle_mouse_event(mouse_event: MouseEvent, game: &mut MathGame) {
+    if game.confirm.is_some() {
+        return;
+    }
+    let position = Position::new(mouse_event.column, mouse_event.row);
+    let click = mouse_event.kind == MouseEventKind::Down(MouseButton::Left);
+    match game.gamestate {
+        GameState::Setup if click => {
+            if let Some(action) = game.click_areas.splash_option(position) {
+                handle_splash_action(game, action);
+            }
+        }
+        GameState::EndingSplash | GameState::HistorySplash => {
+            handle_mouse_event_results(game, mouse_event.kind, position)
+        }
+        _ => {}
+    }
+}
+
+fn handle_mouse_event_results(game: &mut MathGame, kind: MouseEventKind, position: Position) {
+    let areas = &game.click_areas;
+    let on_history = game.gamestate == GameState::HistorySplash
+        && areas.history_table.is_some_and(|a| a.contains(position));
+    let on_questions = areas.question_table.is_some_and(|a| a.contains(position));
+    match kind {
+        MouseEventKind::ScrollUp if on_history => game.history_table_state.select_previous(),
+        MouseEventKind::ScrollDown if on_history => game.history_table_state.select_next(),
+        MouseEventKind::ScrollUp if on_questions => game.result_table_state.select_previous(),
+        MouseEventKind::ScrollDown if on_questions => game.result_table_state.select_next(),
+        MouseEventKind::Down(MouseButton::Left) if on_history => {
+            let row = mouse::table_row(
+                areas.history_table,
+                table::HISTORY_HEADER_HEIGHT,
+                &game.history_table_state,
+                position,
+            );
+            if let Some(row) = row.filter(|r| *r < game.game_history.history.len()) {
+                game.history_table_state.select(Some(row));
+            }
+        }
+        MouseEventKind::Down(MouseButton::Left) => {
+            // a question is picked from its table row or its bar in the chart
+            let question = match on_questions {
+                true => mouse::table_row(
+                    areas.question_table,
+                    table::QUESTION_HEADER_HEIGHT,
+                    &game.result_table_state,
+                    position,
+                ),
+                false => areas.bar(position),
+            };
+            if let Some(question) = question.filter(|q| *q < util::get_target_answers(game).len()) {
+                game.result_table_state.select(Some(question));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_event_confirm(game: &mut MathGame, key_event: KeyEvent) {
     match key_event.code {
-        KeyCode::Char('q') => game.exit(),
-        KeyCode::Char('d') => game.handle_return_to_splash(),
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            if let Some(action) = game.handle_confirm_close() {
+                handle_game_action(game, action);
+            }
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            // declining to resume abandons the checkpointed game
+            let action = game.handle_confirm_close();
+            if action == Some(Action::Resume) {
+                game.handle_resume(false);
+            }
+        }
+        _ => {}
+    }
+}
+
+// restart, end, return and quit while playing, asking first for the actions set to be confirmed
+fn request_game_action(game: &mut MathGame, action: Action) {
+    if game.gameconfig.confirm.contains(&action) {
+        game.handle_confirm_request(action);
+    } else {
+        handle_game_action(game, action);
+    }
+}
+
+fn handle_game_action(game: &mut MathGame, action: Action) {
+    match action {
+        Action::Quit => game.exit(),
+        Action::Restart => game.handle_game_restart(),
+        Action::EndGame => game.handle_game_end(true),
+        Action::ReturnToStart => game.handle_return_to_splash(),
+        Action::Resume => game.handle_resume(true),
+        _ => {}
+    }
+}
 
+fn handle_end_event_splash(game: &mut MathGame, key_event: KeyEvent) {
+    match game.keymap.action(Screen::Results, key_event) {
+        Some(Action::Quit) => return game.exit(),
+        Some(Action::ReturnToStart) => return game.handle_return_to_splash(),
+        Some(Action::NextPlayer) => return game.handle_hotseat_next_player(),
+        _ => {}
+    }
+    match key_event.code {
         KeyCode::Up => game.result_table_state.select_previous(),
         KeyCode::Down => game.result_table_state.select_next(),
         _ => {}
     }
 }
 
-fn handle_key_event_history(game: &mut MathGame, key_event: KeyEvent) {
+// flash-sum and digit span screens are timed off the clock and redrawn every tick,
+// keys only type the answer once the numbers are hidden
+fn handle_key_event_drill(game: &mut MathGame, key_event: KeyEvent) {
+    let answering = game.flash_sum.as_ref().is_some_and(|f| f.is_answering())
+        || game.digit_span.as_ref().is_some_and(|d| d.is_answering())
+        || game.market.is_some();
+    if let Some(action) = game.keymap.action(Screen::Drill, key_event) {
+        return request_game_action(game, action);
+    }
     match key_event.code {
-        KeyCode::Char('q') => game.exit(),
-        KeyCode::Char('d') => game.handle_return_to_splash(),
+        _ if !answering => {}
 
+        KeyCode::Enter if game.flash_sum.is_some() => game.handle_flash_sum_submit(),
+        KeyCode::Enter if game.market.is_some() => game.handle_market_quote(),
+        KeyCode::Enter => game.handle_digit_span_submit(),
+        // market quotes are typed as a bid and an ask
+        _ if game.market.is_some() => game.input.handle_key(key_event, InputKind::Quote),
+        _ if game.flash_sum.is_some() => game.input.handle_key(key_event, InputKind::Integer),
+        _ => game.input.handle_key(key_event, InputKind::Digits),
+    }
+}
+
+fn handle_key_event_versus(game: &mut MathGame, key_event: KeyEvent) {
+    match game.keymap.action(Screen::Report, key_event) {
+        Some(Action::Quit) => game.exit(),
+        Some(Action::ReturnToStart) => game.handle_return_to_splash(),
+        _ => {}
+    }
+}
+
+fn handle_key_event_resting(game: &mut MathGame, key_event: KeyEvent) {
+    match game.keymap.action(Screen::Resting, key_event) {
+        Some(Action::Quit) => game.exit(),
+        Some(Action::ReturnToStart) => game.handle_return_to_splash(),
+        Some(Action::StartNow) => game.update_rest(true),
+        _ => {}
+    }
+}
+
+fn handle_key_event_history(game: &mut MathGame, key_event: KeyEvent) {
+    match game.keymap.action(Screen::History, key_event) {
+        Some(Action::Quit) => return game.exit(),
+        Some(Action::ReturnToStart) => return game.handle_return_to_splash(),
+        Some(Action::GhostRace) => return game.handle_ghost_race_start(),
+        _ => {}
+    }
+    match key_event.code {
         KeyCode::Up => game.history_table_state.select_previous(),
         KeyCode::Down => game.history_table_state.select_next(),
         _ => {}
     }
 }
 
-fn handle_key_event_splash(game: &mut MathGame, key_event: KeyEvent) {
+fn handle_key_event_settings(game: &mut MathGame, key_event: KeyEvent) {
+    match game.keymap.action(Screen::Settings, key_event) {
+        Some(Action::Quit) => return game.exit(),
+        Some(Action::ReturnToStart) => return game.handle_return_to_splash(),
+        _ => {}
+    }
     match key_event.code {
-        KeyCode::Char('q') => game.exit(),
-        KeyCode::Char('s') => {
-            game.gamestate = GameState::Inprogress;
-            game.start_time = Local::now();
-            game.current_question = MathQuestion::generate_new_question(&game.gameconfig.qr)
-        }
-        KeyCode::Char('h') => {
-            game.gamestate = GameState::HistorySplash;
-        }
+        KeyCode::Up => game.settings_table_state.select_previous(),
+        KeyCode::Down => game.settings_table_state.select_next(),
+        KeyCode::Left => game.handle_settings_change(-1),
+        KeyCode::Right | KeyCode::Enter => game.handle_settings_change(1),
+        _ => {}
+    }
+}
 
-        KeyCode::Delete => {
-            game.input.pop();
+fn handle_key_event_lobby(game: &mut MathGame, key_event: KeyEvent) {
+    let is_host = game.multiplayer.as_ref().is_some_and(|m| m.is_host);
+    match game.keymap.action(Screen::Lobby, key_event) {
+        Some(Action::Quit) => game.exit(),
+        Some(Action::StartMatch) if is_host => game.handle_match_start(),
+        Some(Action::Settings) if is_host => {
+            game.gamestate = GameState::SettingsSpash;
         }
         _ => {}
     }
+}
+
+fn handle_key_event_splash(game: &mut MathGame, key_event: KeyEvent) {
+    if let Some(action) = game.keymap.action(Screen::Splash, key_event) {
+        handle_splash_action(game, action);
+    }
 
     // let solved = game.input.parse::<i32>() == Ok(game.current_question.answer);
     // // correct answer,
@@ -75,29 +273,97 @@ fn handle_key_event_splash(game: &mut MathGame, key_event: KeyEvent) {
     // }
 }
 
-fn handle_key_event_game(game: &mut MathGame, key_event: KeyEvent) {
-    match key_event.code {
-        KeyCode::Char('q') => game.exit(),
-        KeyCode::Char('r') => game.handle_game_restart(),
-        KeyCode::Char('e') => game.handle_game_end(true),
-        KeyCode::Char('d') => game.handle_return_to_splash(),
-
-        KeyCode::Backspace => {
-            let _ = &game.input.pop();
+// splash menu options, picked by key or by clicking them
+fn handle_splash_action(game: &mut MathGame, action: Action) {
+    match action {
+        Action::Quit => game.exit(),
+        Action::Start => game.handle_game_start(),
+        Action::Versus => game.handle_hotseat_start(),
+        Action::MockTest => game.handle_session_start(Session::mock_test(&game.gameconfig)),
+        Action::Intervals => game.handle_interval_start(),
+        Action::History => {
+            game.gamestate = GameState::HistorySplash;
         }
-        KeyCode::Delete => {
-            let _ = &game.input.pop();
+        Action::Settings => {
+            game.gamestate = GameState::SettingsSpash;
+        }
+
+        _ => {}
+    }
+}
+
+fn handle_key_event_game(game: &mut MathGame, key_event: KeyEvent) {
+    // option keys take precedence over the other game keys in multiple choice
+    if game.paused_at.is_none() && game.flash.is_none() {
+        if let KeyCode::Char(c) = key_event.code {
+            if let Some(option) = game.current_question.option_for_key(c) {
+                if option == game.current_question.answer {
+                    game.resolve_question(AnswerOutcome::Correct);
+                } else {
+                    game.resolve_question(AnswerOutcome::Wrong);
+                }
+                if game.is_game_over() {
+                    game.handle_game_end(true);
+                }
+                return;
+            }
         }
+    }
 
-        _ => game.input.push_str(&key_event.code.to_string()),
+    let estimating = game.current_question.estimate.is_some() && game.paused_at.is_none();
+    let kind = game.current_question.input_kind();
+    // a bound key that the answer can contain is typed once an answer has been started,
+    // so e is the exponent of an estimate and r separates a quotient from its remainder
+    let typing = game.paused_at.is_none()
+        && !game.input.is_empty()
+        && matches!(key_event.code, KeyCode::Char(c) if kind.allows(c));
+    let action = match typing {
+        true => None,
+        false => game.keymap.action(Screen::Game, key_event),
     };
+    match action {
+        Some(
+            action @ (Action::Quit | Action::Restart | Action::EndGame | Action::ReturnToStart),
+        ) => request_game_action(game, action),
+
+        Some(Action::Pause) => game.handle_pause_toggle(),
+
+        // answers are ignored while paused or while a timed out answer is on screen
+        _ if game.paused_at.is_some() || game.flash.is_some() => {}
+
+        Some(Action::Skip) => game.handle_question_skip(),
+
+        // typed answers are not used for multiple choice questions
+        _ if game.current_question.options.is_some() => {}
+
+        _ => match key_event.code {
+            KeyCode::Enter if estimating => game.handle_estimate_submit(),
+            // submitting an answer that has not already auto-completed means it is wrong
+            KeyCode::Enter
+                if !game.input.is_empty() && !game.current_question.is_correct(&game.input) =>
+            {
+                game.resolve_question(AnswerOutcome::Wrong)
+            }
+
+            KeyCode::Enter => {}
+
+            // editing keys, and only the characters the question's answer can contain
+            _ => game.input.handle_key(key_event, kind),
+        },
+    };
+    if game.gamestate != GameState::Inprogress {
+        return;
+    }
     // check to see if most recent input has solved the question
-    let solved = game.input.parse::<i32>() == Ok(game.current_question.answer);
-    if solved {
-        game.score += 1;
-        game.input.clear();
-        game.current_question.question_answer = Some(Local::now());
-        game.questions.push(game.current_question);
-        game.current_question = MathQuestion::generate_new_question(&game.gameconfig.qr);
+    if game.current_question.is_correct(&game.input) {
+        game.resolve_question(AnswerOutcome::Correct);
+    } else if game.current_question.sign == Sign::Prime
+        && ["y", "n"].contains(&game.input.to_ascii_lowercase().as_str())
+    {
+        // a yes or no answer is final as soon as it is typed
+        game.resolve_question(AnswerOutcome::Wrong);
+    }
+    if game.is_game_over() {
+        game.handle_game_end(true);
     }
 }
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Local};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfiguration;
+
+// blank time between two flashed numbers, so repeats can be told apart
+const FLASH_GAP_MS: i64 = 150;
+
+// one flashed sequence and the sum given for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashRound {
+    pub numbers: Vec<i32>,
+    pub answer: Option<i32>,
+    // time from the sequence finishing to the answer being submitted
+    pub duration_m: i64,
+}
+
+impl FlashRound {
+    pub fn sum(&self) -> i32 {
+        self.numbers.iter().sum()
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.answer == Some(self.sum())
+    }
+}
+
+// a flash-sum game, numbers are shown one at a time and their sum is asked for
+#[derive(Debug, Clone)]
+pub struct FlashSum {
+    pub rounds: Vec<FlashRound>,
+    pub numbers: Vec<i32>,
+    pub round_start: DateTime<Local>,
+    // set while a confirmation is shown, the sequence stops at this instant
+    pub held_at: Option<DateTime<Local>>,
+    pub total_rounds: usize,
+    pub flash_ms: i64,
+}
+
+impl FlashSum {
+    pub fn new(config: &GameConfiguration, rng: &mut StdRng) -> FlashSum {
+        let mut game = FlashSum {
+            rounds: vec![],
+            numbers: vec![],
+            round_start: Local::now(),
+            held_at: None,
+            total_rounds: config.flash_rounds.max(1) as usize,
+            flash_ms: config.flash_ms,
+        };
+        game.next_round(config, rng);
+        game
+    }
+
+    fn next_round(&mut self, config: &GameConfiguration, rng: &mut StdRng) {
+        let digits = config.flash_digits.clamp(1, 6) as u32;
+        let lower = 10_i32.pow(digits - 1);
+        let upper = 10_i32.pow(digits);
+        self.numbers = (0..config.flash_count.max(1))
+            .map(|_| rng.gen_range(lower..upper))
+            .collect();
+        self.round_start = Local::now();
+    }
+
+    fn elapsed_ms(&self) -> i64 {
+        (self.held_at.unwrap_or(Local::now()) - self.round_start).num_milliseconds()
+    }
+
+    pub fn hold(&mut self) {
+        self.held_at.get_or_insert(Local::now());
+    }
+
+    // carry on from where the sequence stopped
+    pub fn release(&mut self) {
+        if let Some(held_at) = self.held_at.take() {
+            self.round_start += Local::now() - held_at;
+        }
+    }
+
+    // the number to show right now, None during the gaps and once the sequence is over
+    pub fn visible_number(&self) -> Option<i32> {
+        let elapsed = self.elapsed_ms();
+        let slot = self.flash_ms + FLASH_GAP_MS;
+        let index = (elapsed / slot) as usize;
+        if elapsed % slot < self.flash_ms {
+            return self.numbers.get(index).copied();
+        }
+        None
+    }
+
+    // index of the number being flashed, used for the progress counter
+    pub fn flash_index(&self) -> usize {
+        (self.elapsed_ms() / (self.flash_ms + FLASH_GAP_MS)) as usize
+    }
+
+    // true once every number has been flashed and the sum can be typed
+    pub fn is_answering(&self) -> bool {
+        self.flash_index() >= self.numbers.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.rounds.len() >= self.total_rounds
+    }
+
+    // record the sum given for this round and start the next one
+    pub fn submit(&mut self, input: &str, config: &GameConfiguration, rng: &mut StdRng) {
+        let sequence_ms = self.numbers.len() as i64 * (self.flash_ms + FLASH_GAP_MS);
+        self.rounds.push(FlashRound {
+            numbers: self.numbers.clone(),
+            answer: input.trim().parse().ok(),
+            duration_m: self.elapsed_ms() - sequence_ms,
+        });
+        if !self.is_finished() {
+            self.next_round(config, rng);
+        }
+    }
+
+    pub fn score(&self) -> i32 {
+        self.rounds.iter().filter(|r| r.is_correct()).count() as i32
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Local, Utc};
-use rand::Rng;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use std::error;
@@ -10,27 +10,53 @@ pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 use ratatui::{
     buffer::Buffer,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::Stylize,
+    style::{Style, Stylize},
     symbols::border,
-    text::{Line, Span, Text},
+    text::{Line, Text},
     widgets::{
         block::{Position, Title},
-        Block, Paragraph, TableState, Widget,
+        Block, Gauge, LineGauge, Paragraph, TableState, Widget,
     },
     Frame,
 };
 
 use crate::{
-    config::{GameConfiguration, QuestionRanges},
+    checkpoint::{Checkpoint, CHECKPOINT_INTERVAL_MS, CHECKPOINT_PATH},
+    config::{
+        DivisionFormat, EndSettings, GameConfiguration, GameMode, QuestionRanges,
+        QuestionSettings, Setting,
+    },
+    confirm::Confirm,
+    flash::FlashSum,
+    ghost::Ghost,
+    net::{Multiplayer, NetMessage, COUNTDOWN_S},
+    session::{IntervalPlan, Session},
+    span::DigitSpan,
+    versus::Hotseat,
     history::{GameHistory, GameRecord},
+    input::{InputEditor, InputKind},
+    keymap::{Action, Keymap, Screen},
+    market::Market,
+    mastery::MasteryGrid,
+    mouse::ClickAreas,
     util::{self, Sign},
 };
 
 #[derive(Debug, PartialEq)]
 pub enum GameState {
     Setup,
+    Lobby,
     Inprogress,
+    FlashSum,
     EndingSplash,
+    FlashResults,
+    DigitSpan,
+    SpanResults,
+    Market,
+    MarketResults,
+    VersusSplash,
+    Resting,
+    SessionSplash,
     HistorySplash,
     SettingsSpash,
 }
@@ -40,17 +66,64 @@ pub struct MathGame {
     pub current_question: MathQuestion,
     // game_is_started: bool,
     pub exit: bool,
-    pub input: String,
+    pub input: InputEditor,
     pub score: i32,
+    pub lives: i32,
+    // consecutive fast correct answers, and the points they have built up
+    pub streak: i32,
+    pub best_streak: i32,
+    pub combo_score: i32,
+    // a timed out question whose answer is being shown before play continues
+    pub flash: Option<MathQuestion>,
     pub start_time: DateTime<Local>,
+    // set while the game is paused, the clocks are frozen at this instant
+    pub paused_at: Option<DateTime<Local>>,
+    // total time spent paused this game
+    pub paused_ms: i64,
+    // set while a confirmation holds the clocks, the hold is not counted as a pause
+    pub held_at: Option<DateTime<Local>>,
+    // total time the clocks were held by confirmations this game
+    pub held_ms: i64,
     pub current_time: DateTime<Local>,
-    pub questions: Vec<MathQuestion>,
     pub answers: Vec<MathAnswer>,
     pub gamestate: GameState,
     pub gameconfig: GameConfiguration,
     pub result_table_state: TableState,
     pub game_history: GameHistory,
     pub history_table_state: TableState,
+    pub settings_table_state: TableState,
+    // seed and generator for the current game's question sequence
+    pub seed: u64,
+    pub rng: StdRng,
+    // a past game being raced, if any
+    pub ghost: Option<Ghost>,
+    // a two player hot-seat game, if one is being played
+    pub hotseat: Option<Hotseat>,
+    // a networked match, set from the command line
+    pub multiplayer: Option<Multiplayer>,
+    // a multi-section session, such as a mock test, being played
+    pub session: Option<Session>,
+    // set while playing or reviewing a flash-sum game
+    pub flash_sum: Option<FlashSum>,
+    // set while playing or reviewing a digit span game
+    pub digit_span: Option<DigitSpan>,
+    // set while playing or reviewing a market making game
+    pub market: Option<Market>,
+    // times table progress while playing the mastery mode
+    pub mastery: Option<MasteryGrid>,
+    // the grid shown on the history screen and how many games it was built from
+    pub history_mastery: Option<(usize, MasteryGrid)>,
+    // keys bound to each screen's actions
+    pub keymap: Keymap,
+    // where the clickable parts of the current screen were drawn
+    pub click_areas: ClickAreas,
+    // a destructive action being confirmed, shown over the current screen
+    pub confirm: Option<Confirm>,
+    // an unfinished game from the last run, waiting to be resumed or declined
+    pub checkpoint: Option<Checkpoint>,
+    pub checkpointed_at: DateTime<Local>,
+    // the game being played was picked up from a checkpoint
+    pub resumed: bool,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -61,6 +134,46 @@ pub struct MathQuestion {
     pub sign: Sign,
     pub question_start: DateTime<Local>,
     pub question_answer: Option<DateTime<Local>>,
+    // answer options 1-5 when playing multiple choice
+    #[serde(default)]
+    pub options: Option<[i32; 5]>,
+    // operands and answer of an estimation question, which are too large for the integer fields
+    #[serde(default)]
+    pub estimate: Option<Estimate>,
+    // how a division with a remainder is to be answered, None for exact division
+    #[serde(default)]
+    pub division: Option<DivisionFormat>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Estimate {
+    pub lhs: f64,
+    pub rhs: f64,
+    pub answer: f64,
+}
+
+// how a question was resolved, records saved before outcomes existed are migrated on load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum AnswerOutcome {
+    #[default]
+    Correct,
+    Wrong,
+    TimedOut,
+    Skipped,
+    // the question on screen when the game ended
+    Unanswered,
+}
+
+impl AnswerOutcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnswerOutcome::Correct => "Correct",
+            AnswerOutcome::Wrong => "Wrong",
+            AnswerOutcome::TimedOut => "Timeout",
+            AnswerOutcome::Skipped => "Skipped",
+            AnswerOutcome::Unanswered => "-",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,63 +182,810 @@ pub struct MathAnswer {
     pub string_representation: String,
     pub duration_s: i64,
     pub duration_m: i64,
+    #[serde(default)]
+    pub outcome: AnswerOutcome,
+    // how many times too large or too small an estimate was
+    #[serde(default)]
+    pub error_factor: Option<f64>,
+    // game clock in milliseconds when the question was closed, including any penalties
+    #[serde(default)]
+    pub elapsed_m: Option<i64>,
 }
 
 impl Default for MathGame {
     fn default() -> Self {
         let config = GameConfiguration::default();
-        let first_question = MathQuestion::generate_new_question(&config.qr);
+        let seed = rand::thread_rng().gen();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let first_question =
+            MathQuestion::generate_new_question(&config.qr, &config.signs, &mut rng);
         Self {
             current_question: first_question,
             // game_is_started: Default::default(),
             exit: Default::default(),
             input: Default::default(),
             score: Default::default(),
+            lives: config.lives,
+            streak: 0,
+            best_streak: 0,
+            combo_score: 0,
+            flash: None,
             start_time: Local::now(),
+            paused_at: None,
+            paused_ms: 0,
+            held_at: None,
+            held_ms: 0,
             current_time: Local::now(),
-            questions: vec![],
             answers: vec![],
             gamestate: GameState::Setup,
             gameconfig: config,
             result_table_state: TableState::default().with_selected(0),
             game_history: GameHistory::new("results.json").unwrap_or_default(),
             history_table_state: TableState::default().with_selected(0),
+            settings_table_state: TableState::default().with_selected(0),
+            seed,
+            rng,
+            ghost: None,
+            hotseat: None,
+            multiplayer: None,
+            session: None,
+            flash_sum: None,
+            digit_span: None,
+            market: None,
+            mastery: None,
+            history_mastery: None,
+            keymap: Keymap::default(),
+            click_areas: ClickAreas::default(),
+            confirm: None,
+            checkpoint: None,
+            checkpointed_at: Local::now(),
+            resumed: false,
         }
     }
 }
 
 impl MathGame {
     pub fn get_elapsed_time_seconds(&self) -> i32 {
-        return (Local::now() - self.start_time).num_seconds() as i32;
+        (self.get_elapsed_time_ms() / 1000) as i32
+    }
+
+    // game time in milliseconds, excluding any time spent paused or held
+    pub fn get_elapsed_time_ms(&self) -> i64 {
+        (self.clock_now() - self.start_time).num_milliseconds() - self.paused_ms - self.held_ms
+    }
+
+    // the instant the game clocks read, frozen while paused or held
+    fn clock_now(&self) -> DateTime<Local> {
+        self.paused_at.or(self.held_at).unwrap_or(Local::now())
+    }
+
+    // a networked match can't be paused, everyone else is still playing
+    pub fn handle_pause_toggle(&mut self) {
+        if self.multiplayer.is_some() {
+            return;
+        }
+        match self.paused_at {
+            Some(_) => self.resume(),
+            None => self.paused_at = Some(Local::now()),
+        }
+    }
+
+    // Ask before a destructive action. The clocks are held while the question is shown,
+    // without marking the game as paused, but a networked match carries on for everyone else
+    pub fn handle_confirm_request(&mut self, action: Action) {
+        let held = self.is_playing() && self.paused_at.is_none() && self.multiplayer.is_none();
+        if held {
+            self.held_at = Some(Local::now());
+            if let Some(flash_sum) = self.flash_sum.as_mut() {
+                flash_sum.hold();
+            }
+            if let Some(digit_span) = self.digit_span.as_mut() {
+                digit_span.hold();
+            }
+        }
+        self.confirm = Some(Confirm { action, held });
+    }
+
+    // close the confirmation, returning the action asked about
+    pub fn handle_confirm_close(&mut self) -> Option<Action> {
+        let confirm = self.confirm.take()?;
+        if confirm.held {
+            if let Some(held_at) = self.held_at.take() {
+                let hold = Local::now() - held_at;
+                self.held_ms += hold.num_milliseconds();
+                self.shift_question_clocks(hold);
+            }
+            if let Some(flash_sum) = self.flash_sum.as_mut() {
+                flash_sum.release();
+            }
+            if let Some(digit_span) = self.digit_span.as_mut() {
+                digit_span.release();
+            }
+        }
+        Some(confirm.action)
+    }
+
+    // unfreeze the clocks, pushing the current question's start back by the pause length
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            let pause = Local::now() - paused_at;
+            self.paused_ms += pause.num_milliseconds();
+            self.shift_question_clocks(pause);
+        }
+    }
+
+    fn shift_question_clocks(&mut self, by: chrono::Duration) {
+        self.current_question.question_start += by;
+        if let Some(flashed) = self.flash.as_mut() {
+            flashed.question_answer = flashed.question_answer.map(|t| t + by);
+        }
     }
     pub fn handle_game_start(&mut self) {
+        // bindings that clash under these settings would swallow answers, so they are shown
+        // on the settings screen to be fixed first. A match has to start with everyone else
+        if self.multiplayer.is_none() && !self.keymap.validate(&self.gameconfig).is_empty() {
+            self.gamestate = GameState::SettingsSpash;
+            return;
+        }
+        // ghost races replay the raced game's questions when it was seeded
+        self.seed = self
+            .ghost
+            .as_ref()
+            .and_then(|g| g.seed)
+            .or(self.hotseat.as_ref().map(|h| h.seed()))
+            .or(self.multiplayer.as_ref().and_then(|m| m.seed))
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.resumed = false;
         self.score = 0;
+        self.lives = self.gameconfig.lives;
+        self.streak = 0;
+        self.best_streak = 0;
+        self.combo_score = 0;
+        self.flash = None;
+        self.input.clear();
         self.answers = vec![];
-        self.questions = vec![];
-        self.current_question = MathQuestion::generate_new_question(&self.gameconfig.qr);
+        self.mastery = (self.gameconfig.mode == GameMode::Mastery).then(|| {
+            MasteryGrid::from_history(
+                &self.game_history,
+                self.gameconfig.table_lower,
+                self.gameconfig.table_upper,
+                self.gameconfig.mastery_ms,
+            )
+        });
+        self.current_question = self.next_question(None);
         self.current_time = Local::now();
         self.start_time = Local::now();
+        self.paused_at = None;
+        self.paused_ms = 0;
+        self.held_at = None;
+        self.held_ms = 0;
+        self.flash_sum = None;
+        self.digit_span = None;
+        self.market = None;
         self.gamestate = GameState::Inprogress;
+        if self.gameconfig.mode == GameMode::FlashSum {
+            self.flash_sum = Some(FlashSum::new(&self.gameconfig, &mut self.rng));
+            self.gamestate = GameState::FlashSum;
+        }
+        if self.gameconfig.mode == GameMode::DigitSpan {
+            self.digit_span = Some(DigitSpan::new(&self.gameconfig, &mut self.rng));
+            self.gamestate = GameState::DigitSpan;
+        }
+        if self.gameconfig.mode == GameMode::Market {
+            self.market = Some(Market::new(&self.gameconfig, &mut self.rng));
+            self.gamestate = GameState::Market;
+        }
+    }
+
+    // answer the current flash-sum round, the game ends after the last one
+    pub fn handle_flash_sum_submit(&mut self) {
+        let Some(flash_sum) = self.flash_sum.as_mut() else {
+            return;
+        };
+        if !flash_sum.is_answering() || self.input.is_empty() {
+            return;
+        }
+        flash_sum.submit(&self.input, &self.gameconfig, &mut self.rng);
+        self.score = flash_sum.score();
+        self.input.clear();
+        if flash_sum.is_finished() {
+            self.handle_game_end(true);
+        }
+    }
+
+    // answer the current digit span trial, the game ends after the last one
+    pub fn handle_digit_span_submit(&mut self) {
+        let Some(digit_span) = self.digit_span.as_mut() else {
+            return;
+        };
+        if !digit_span.is_answering() || self.input.is_empty() {
+            return;
+        }
+        digit_span.submit(&self.input, &mut self.rng);
+        self.score = digit_span.max_span() as i32;
+        self.input.clear();
+        if digit_span.is_finished() {
+            self.handle_game_end(true);
+        }
+    }
+
+    // quote the current market making round, rejected quotes are left in the input to fix
+    pub fn handle_market_quote(&mut self) {
+        let Some(market) = self.market.as_mut() else {
+            return;
+        };
+        if !market.quote(&self.input, &mut self.rng) {
+            return;
+        }
+        self.score = market.pnl();
+        self.input.clear();
+        if market.is_finished() {
+            self.handle_game_end(true);
+        }
+    }
+
+    // race a previous game from the history screen in the mode it was played in
+    pub fn handle_ghost_race_start(&mut self) {
+        let Some(record) = util::get_selected_record(self) else {
+            return;
+        };
+        // flash-sum, digit span and market games have no question timeline to race
+        if matches!(
+            record.mode,
+            GameMode::FlashSum | GameMode::DigitSpan | GameMode::Market
+        ) {
+            return;
+        }
+        // the race is played on the raced game's settings, yours come back afterwards
+        let base_config = match &self.ghost {
+            Some(previous) => previous.base_config.clone(),
+            None => self.gameconfig.clone(),
+        };
+        let ghost = Ghost::from_record(record, base_config);
+        self.gameconfig.mode = ghost.record.mode;
+        if let Some(settings) = &ghost.record.question_settings {
+            settings.apply(&mut self.gameconfig);
+        }
+        if let Some(settings) = &ghost.record.end_settings {
+            settings.apply(&mut self.gameconfig);
+        }
+        self.ghost = Some(ghost);
+        self.handle_game_start();
+    }
+
+    // start a hot-seat game, player one goes first
+    pub fn handle_hotseat_start(&mut self) {
+        self.hotseat = Some(Hotseat::new(rand::thread_rng().gen()));
+        self.handle_game_start();
+    }
+
+    // hand over to the next hot-seat player once the previous one has seen their results
+    pub fn handle_hotseat_next_player(&mut self) {
+        if let Some(hotseat) = self.hotseat.as_mut() {
+            if hotseat.next_player_waiting() {
+                hotseat.player += 1;
+                self.handle_game_start();
+            }
+        }
+    }
+
+    // host only, pick a seed and broadcast the config so every player starts together
+    pub fn handle_match_start(&mut self) {
+        let Some(multiplayer) = self.multiplayer.as_ref() else {
+            return;
+        };
+        // restarting would pull players still in the last match back to the lobby
+        if !multiplayer.is_host
+            || multiplayer.start_at.is_some()
+            || multiplayer.match_in_progress()
+        {
+            return;
+        }
+        let start = NetMessage::Start {
+            match_id: format!("{}-{:04x}", Utc::now().format("%Y%m%d%H%M%S"), rand::random::<u16>()),
+            seed: rand::thread_rng().gen(),
+            config: Box::new(self.gameconfig.clone()),
+            countdown_ms: COUNTDOWN_S * 1000,
+        };
+        multiplayer.send(start.clone());
+        self.handle_net_message(start);
+    }
+
+    // drain messages from the network, called every tick
+    pub fn handle_network_messages(&mut self) {
+        let mut messages = vec![];
+        if let Some(multiplayer) = self.multiplayer.as_mut() {
+            while let Some(message) = multiplayer.try_recv() {
+                messages.push(message);
+            }
+        }
+        for message in messages {
+            self.handle_net_message(message);
+        }
+    }
+
+    fn handle_net_message(&mut self, message: NetMessage) {
+        // a start from a host that did not wait for us ends our game with what we had scored
+        if matches!(message, NetMessage::Start { .. }) && self.is_playing() {
+            self.handle_game_end(true);
+        }
+        let Some(multiplayer) = self.multiplayer.as_mut() else {
+            return;
+        };
+        match message {
+            NetMessage::Hello { name, id } => {
+                let name = multiplayer.add_client(&name, id);
+                multiplayer.send(NetMessage::Welcome { id, name });
+                let players = multiplayer.players.iter().map(|p| p.name.clone()).collect();
+                multiplayer.send(NetMessage::Lobby { players });
+            }
+            // welcomes are broadcast, so the name is only taken when the id was the one we sent
+            NetMessage::Welcome { id, name } => {
+                if multiplayer.hello_id == Some(id) && name != multiplayer.name {
+                    let own = multiplayer.name.clone();
+                    multiplayer.player_mut(&own).name = name.clone();
+                    multiplayer.name = name;
+                }
+            }
+            NetMessage::Lobby { players } => {
+                for name in players.iter() {
+                    multiplayer.player_mut(name);
+                }
+            }
+            NetMessage::Start {
+                match_id,
+                seed,
+                config,
+                countdown_ms,
+            } => {
+                multiplayer.match_id = Some(match_id);
+                multiplayer.seed = Some(seed);
+                multiplayer.start_at = Some(Utc::now() + chrono::Duration::milliseconds(countdown_ms));
+                multiplayer.reset_scores();
+                // confirmations and keeping abandoned games stay each player's own choice
+                self.gameconfig = GameConfiguration {
+                    confirm: std::mem::take(&mut self.gameconfig.confirm),
+                    save_abandoned: self.gameconfig.save_abandoned,
+                    ..*config
+                };
+                self.gamestate = GameState::Lobby;
+            }
+            NetMessage::Score {
+                ref player,
+                score,
+                correct,
+            } => {
+                if *player != multiplayer.name {
+                    let status = multiplayer.player_mut(player);
+                    status.score = score;
+                    status.correct = correct;
+                    if multiplayer.is_host {
+                        multiplayer.send(message.clone());
+                    }
+                }
+            }
+            NetMessage::Finished {
+                ref player,
+                score,
+                correct,
+                duration_m,
+            } => {
+                if *player != multiplayer.name {
+                    let status = multiplayer.player_mut(player);
+                    status.score = score;
+                    status.correct = correct;
+                    status.duration_m = duration_m;
+                    status.finished = true;
+                    if multiplayer.is_host {
+                        multiplayer.send(message.clone());
+                    }
+                }
+            }
+            // the host works out who left from their connection, so never trusts a client's word
+            NetMessage::Left { ref player } => {
+                if !multiplayer.is_host {
+                    multiplayer.remove_player(player);
+                }
+            }
+            NetMessage::Dropped { id } => {
+                if let Some(player) = multiplayer.players.iter().find(|p| p.id == Some(id)) {
+                    let player = player.name.clone();
+                    multiplayer.remove_player(&player);
+                    multiplayer.send(NetMessage::Left { player });
+                }
+            }
+            // everyone else was relayed through the host
+            NetMessage::Closed => {
+                let name = multiplayer.name.clone();
+                multiplayer.players.retain(|p| p.name == name);
+            }
+        }
+    }
+
+    // begin a networked match once its countdown is over, and stream our score while playing
+    pub fn update_multiplayer(&mut self) {
+        let playing = self.is_playing();
+        let correct = self.correct_count();
+        let Some(multiplayer) = self.multiplayer.as_mut() else {
+            return;
+        };
+        if self.gamestate == GameState::Lobby
+            && multiplayer.start_at.is_some_and(|t| Utc::now() >= t)
+        {
+            multiplayer.start_at = None;
+            self.handle_game_start();
+            return;
+        }
+        if playing && multiplayer.last_sent != (self.score, correct) {
+            multiplayer.last_sent = (self.score, correct);
+            let status = multiplayer.player_mut(&multiplayer.name.clone());
+            status.score = self.score;
+            status.correct = correct;
+            multiplayer.send(NetMessage::Score {
+                player: multiplayer.name.clone(),
+                score: self.score,
+                correct,
+            });
+        }
+    }
+
+    pub fn handle_session_start(&mut self, session: Session) {
+        self.session = Some(session);
+        self.start_current_section();
+    }
+
+    // play the session's current section with its own config
+    fn start_current_section(&mut self) {
+        let config = self
+            .session
+            .as_ref()
+            .and_then(|s| s.current_section())
+            .map(|s| s.config.clone());
+        if let Some(config) = config {
+            self.gameconfig = config;
+            self.handle_game_start();
+        }
+    }
+
+    // move on after a section ends, or show the report once every section is done
+    fn advance_session(&mut self) {
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        session.records.push(self.game_history.history.len() - 1);
+        session.current += 1;
+        if session.is_finished() {
+            self.gameconfig = session.base_config.clone();
+            self.gamestate = GameState::SessionSplash;
+        } else if session.rest_s > 0 {
+            session.resting_until = Some(Local::now() + chrono::Duration::seconds(session.rest_s));
+            self.gamestate = GameState::Resting;
+        } else {
+            self.start_current_section();
+        }
+    }
+
+    pub fn handle_interval_start(&mut self) {
+        if let Some(plan) = IntervalPlan::parse(&self.gameconfig.interval_plan) {
+            self.handle_session_start(Session::intervals(plan, &self.gameconfig));
+        }
+    }
+
+    // start the next interval once the rest is over, or straight away when asked
+    pub fn update_rest(&mut self, skip: bool) {
+        if self.gamestate != GameState::Resting {
+            return;
+        }
+        let Some(session) = self.session.as_mut() else {
+            return;
+        };
+        if skip || session.resting_until.is_none_or(|t| Local::now() >= t) {
+            session.resting_until = None;
+            self.start_current_section();
+        }
     }
 
     pub fn handle_return_to_splash(&mut self){
-        self.gamestate = GameState::Setup;
+        self.handle_game_abandon();
+        if let Some(ghost) = self.ghost.take() {
+            self.gameconfig = ghost.base_config;
+        }
+        self.hotseat = None;
+        self.flash_sum = None;
+        self.digit_span = None;
+        self.market = None;
+        if let Some(session) = self.session.take() {
+            self.gameconfig = session.base_config;
+        }
+        self.gamestate = match self.multiplayer {
+            Some(_) => GameState::Lobby,
+            None => GameState::Setup,
+        };
         self.result_table_state.select_first();
     }
 
-    pub fn handle_game_end(&mut self, save: bool) {
+    // close off the current question with the given outcome and move on to a new one
+    pub fn resolve_question(&mut self, outcome: AnswerOutcome) {
+        self.resolve_answer(outcome, None);
+    }
+
+    // resolve a question, submitted estimates carry how far out they were
+    fn resolve_answer(&mut self, outcome: AnswerOutcome, error_factor: Option<f64>) {
         self.current_question.question_answer = Some(Local::now());
-        self.questions.push(self.current_question);
-        self.answers = self
-            .questions
+        let mut answer = self.current_question.generate_math_answer(outcome);
+        answer.error_factor = error_factor;
+        answer.elapsed_m = Some(self.get_elapsed_time_ms());
+        if let Some(mastery) = self.mastery.as_mut() {
+            mastery.record(&answer);
+        }
+        self.answers.push(answer);
+        self.input.clear();
+        self.update_combo(outcome);
+        match outcome {
+            AnswerOutcome::Correct => self.score += 1,
+            AnswerOutcome::Wrong | AnswerOutcome::TimedOut => {
+                if self.gameconfig.mode == GameMode::Survival {
+                    self.lives -= 1;
+                }
+                // negative marking for wrong picks in multiple choice
+                if outcome == AnswerOutcome::Wrong && self.gameconfig.multiple_choice {
+                    self.score -= self.gameconfig.wrong_penalty;
+                }
+            }
+            AnswerOutcome::Skipped | AnswerOutcome::Unanswered => {}
+        }
+        // chains only carry on from an answer the player has seen, a correct one or a flashed timeout
+        let previous = matches!(outcome, AnswerOutcome::Correct | AnswerOutcome::TimedOut)
+            .then_some(self.current_question)
+            .filter(|q| q.sign.continues_chain());
+        self.current_question = self.next_question(previous);
+    }
+
+    // fast correct answers build the streak, anything else breaks it
+    fn update_combo(&mut self, outcome: AnswerOutcome) {
+        if !self.gameconfig.combo_scoring || outcome == AnswerOutcome::Unanswered {
+            return;
+        }
+        let fast = self
+            .answers
+            .last()
+            .is_some_and(|a| a.duration_m <= self.gameconfig.combo_threshold_ms);
+        match outcome {
+            AnswerOutcome::Correct if fast => self.streak += 1,
+            _ => self.streak = 0,
+        }
+        self.best_streak = self.best_streak.max(self.streak);
+        if outcome == AnswerOutcome::Correct {
+            self.combo_score += self.combo_multiplier();
+        }
+    }
+
+    // every three fast answers in a row adds one to the multiplier, up to x5
+    pub fn combo_multiplier(&self) -> i32 {
+        (1 + self.streak / 3).min(5)
+    }
+
+    // generate the next question with the options the current config asks for
+    fn next_question(&mut self, previous: Option<MathQuestion>) -> MathQuestion {
+        let qr = &self.gameconfig.qr;
+        let signs = &self.gameconfig.signs;
+        if self.gameconfig.estimation {
+            return MathQuestion::generate_estimate_question(signs, &mut self.rng);
+        }
+        if let Some(mastery) = &self.mastery {
+            let (a, b) = mastery.next_fact(&mut self.rng);
+            let question = MathQuestion::generate_fact_question(a, b, &mut self.rng);
+            if self.gameconfig.multiple_choice {
+                return question.with_options(&mut self.rng);
+            }
+            return question;
+        }
+        let question = match previous {
+            Some(previous) if self.gameconfig.chained => {
+                MathQuestion::generate_chained_question(&previous, qr, signs, &mut self.rng)
+            }
+            _ => MathQuestion::generate_new_question(qr, signs, &mut self.rng),
+        };
+        // a running total stays whole, so chains only ever divide exactly
+        let question = match self.gameconfig.division_format {
+            DivisionFormat::Exact => question,
+            _ if self.gameconfig.chained => question,
+            format => question.with_remainder(format, &mut self.rng),
+        };
+        if self.gameconfig.multiple_choice {
+            return question.with_options(&mut self.rng);
+        }
+        question
+    }
+
+    // submit an estimate, it is correct when within the configured tolerance
+    pub fn handle_estimate_submit(&mut self) {
+        let Some(factor) = self.current_question.error_factor(&self.input) else {
+            return;
+        };
+        // the score counts estimates within tolerance, closeness points are taken from the factor
+        let tolerance = 1.0 + self.gameconfig.estimate_tolerance_pct as f64 / 100.0;
+        if factor <= tolerance {
+            self.resolve_answer(AnswerOutcome::Correct, Some(factor));
+        } else {
+            self.resolve_answer(AnswerOutcome::Wrong, Some(factor));
+        }
+    }
+
+    // abandon the current question, optionally costing time on the game clock
+    pub fn handle_question_skip(&mut self) {
+        self.resolve_question(AnswerOutcome::Skipped);
+        self.start_time -= chrono::Duration::seconds(self.gameconfig.skip_penalty_s as i64);
+    }
+
+    // the per question deadline in seconds, survival always has one
+    pub fn question_limit(&self) -> Option<i32> {
+        match (self.gameconfig.question_limit, self.gameconfig.mode) {
+            (None, GameMode::Survival) => Some(self.gameconfig.survival_question_limit),
+            (limit, _) => limit,
+        }
+    }
+
+    // time out the current question, its answer is flashed before the next one starts
+    pub fn handle_question_timeout(&mut self) {
+        let timed_out = self.current_question;
+        self.resolve_question(AnswerOutcome::TimedOut);
+        self.flash = Some(timed_out);
+    }
+
+    // clear the flashed answer once it has been shown for long enough
+    pub fn update_flash(&mut self) {
+        let expired = self.flash.is_some_and(|q| {
+            (Local::now() - q.question_answer.unwrap_or(q.question_start)).num_milliseconds()
+                >= self.gameconfig.timeout_flash_ms
+        });
+        if expired {
+            self.flash = None;
+            // the next question's clock starts once it is actually visible
+            self.current_question.question_start = Local::now();
+        }
+    }
+
+    // true once the end condition of the current game mode has been met
+    pub fn is_game_over(&self) -> bool {
+        match self.gameconfig.mode {
+            GameMode::Timed | GameMode::Mastery => {
+                self.get_elapsed_time_seconds() > self.gameconfig.timer
+            }
+            GameMode::Survival => self.lives <= 0,
+            GameMode::Sprint => self.correct_count() >= self.gameconfig.sprint_questions,
+            GameMode::FlashSum => self.flash_sum.as_ref().is_some_and(|f| f.is_finished()),
+            GameMode::DigitSpan => self.digit_span.as_ref().is_some_and(|d| d.is_finished()),
+            GameMode::Market => self.market.as_ref().is_some_and(|m| m.is_finished()),
+        }
+    }
+
+    // questions answered correctly so far, unlike the score this ignores penalties and bonuses
+    pub fn correct_count(&self) -> i32 {
+        self.answers
             .iter()
-            .map(|f| f.generate_math_answer())
-            .collect();
+            .filter(|a| a.outcome == AnswerOutcome::Correct)
+            .count() as i32
+    }
+
+    pub fn handle_game_end(&mut self, save: bool) {
+        self.resume();
+        let match_id = self.report_match_result();
+        self.record_game(match_id, false, save);
+        Checkpoint::remove(CHECKPOINT_PATH);
+
+        self.gamestate = if self.flash_sum.is_some() {
+            GameState::FlashResults
+        } else if self.digit_span.is_some() {
+            GameState::SpanResults
+        } else if self.market.is_some() {
+            GameState::MarketResults
+        } else {
+            GameState::EndingSplash
+        };
+        if let Some(hotseat) = self.hotseat.as_mut() {
+            hotseat.records.push(self.game_history.history.len() - 1);
+            if hotseat.is_finished() {
+                self.gamestate = GameState::VersusSplash;
+            }
+        }
+        self.advance_session();
+    }
+
+    // tell the other players how our game ended, returning the match it was part of
+    fn report_match_result(&mut self) -> Option<String> {
+        let duration_m = self.get_elapsed_time_ms();
+        let correct = self.correct_count();
+        self.multiplayer.as_mut().and_then(|multiplayer| {
+            let name = multiplayer.name.clone();
+            let status = multiplayer.player_mut(&name);
+            status.score = self.score;
+            status.correct = correct;
+            status.duration_m = duration_m;
+            status.finished = true;
+            multiplayer.send(NetMessage::Finished {
+                player: name,
+                score: self.score,
+                correct,
+                duration_m,
+            });
+            multiplayer.match_id.clone()
+        })
+    }
+
+    // a game is left part way through, it is only kept if the settings ask for abandoned games
+    fn handle_game_abandon(&mut self) {
+        if !self.is_playing() {
+            return;
+        }
+        Checkpoint::remove(CHECKPOINT_PATH);
+        self.resume();
+        // a match cannot be restarted until everyone is done, so leaving one counts as finishing
+        let match_id = self.report_match_result();
+        if !self.gameconfig.save_abandoned {
+            return;
+        }
+        self.record_game(match_id, true, true);
+    }
+
+    // whether a game or drill is being played, rather than a menu or results screen
+    pub fn is_playing(&self) -> bool {
+        matches!(
+            self.gamestate,
+            GameState::Inprogress | GameState::FlashSum | GameState::DigitSpan | GameState::Market
+        )
+    }
+
+    // add the game being played to the history, saving it to disk if asked to
+    fn record_game(&mut self, match_id: Option<String>, abandoned: bool, save: bool) {
+        // flash-sum, digit span and market games keep their rounds rather than question answers
+        if self.flash_sum.is_none() && self.digit_span.is_none() && self.market.is_none() {
+            self.current_question.question_answer = Some(Local::now());
+            self.answers.push(
+                self.current_question
+                    .generate_math_answer(AnswerOutcome::Unanswered),
+            );
+        }
+
+        let duration_m = self.get_elapsed_time_ms();
 
         //this will panic if too long. TODO fix
         self.game_history.add_game_result(GameRecord {
             game_intant: Utc::now(),
             score: self.score,
+            mode: self.gameconfig.mode,
+            duration_m,
+            sprint_questions: (self.gameconfig.mode == GameMode::Sprint)
+                .then_some(self.gameconfig.sprint_questions),
+            paused: self.paused_ms > 0,
+            paused_ms: self.paused_ms,
+            // questions after a resume no longer follow the seed, so cannot be replayed
+            seed: (!self.resumed).then_some(self.seed),
+            question_settings: Some(QuestionSettings::from_config(&self.gameconfig)),
+            end_settings: Some(EndSettings::from_config(&self.gameconfig)),
+            match_id,
+            combo_score: self.gameconfig.combo_scoring.then_some(self.combo_score),
+            best_streak: self.best_streak,
+            session_id: self.session.as_ref().map(|s| s.id.clone()),
+            section: self
+                .session
+                .as_ref()
+                .and_then(|s| s.current_section())
+                .map(|s| s.name.clone()),
+            flash_rounds: self
+                .flash_sum
+                .as_ref()
+                .map_or(vec![], |f| f.rounds.clone()),
+            span_trials: self
+                .digit_span
+                .as_ref()
+                .map_or(vec![], |d| d.trials.clone()),
+            max_span: self.digit_span.as_ref().map(|d| d.max_span()),
+            market_rounds: self.market.as_ref().map_or(vec![], |m| m.rounds.clone()),
+            market_value: self.market.as_ref().map(|m| m.value()),
+            abandoned,
+            resumed: self.resumed,
             answers: self.answers.clone(),
         });
         if save {
@@ -135,20 +995,134 @@ impl MathGame {
                 Err(e) => panic!("{}", e),
             }
         }
-        self.gamestate = GameState::EndingSplash;
     }
 
     pub fn handle_game_restart(&mut self) {
+        // a networked match can only be restarted by the host from the lobby
+        if self.multiplayer.is_some() {
+            return;
+        }
+        self.handle_game_abandon();
         let _ = &self.handle_game_start();
     }
 
+    pub fn handle_settings_change(&mut self, delta: i32) {
+        let selected = self.settings_table_state.selected().unwrap_or_default();
+        if let Some(setting) = Setting::ALL.get(selected) {
+            setting.adjust(&mut self.gameconfig, delta);
+        }
+    }
+
     pub fn draw(&self, frame: &mut Frame) {
+        let sprint = self.gameconfig.mode == GameMode::Sprint;
+        let mut constraints = vec![Constraint::Min(0)];
+        if sprint {
+            constraints.push(Constraint::Length(3));
+        }
+        if self.ghost.is_some() {
+            constraints.push(Constraint::Length(4));
+        }
+        if let Some(multiplayer) = &self.multiplayer {
+            constraints.push(Constraint::Length(multiplayer.players.len() as u16 + 2));
+        }
         let layout: std::rc::Rc<[Rect]> =
-            Layout::new(Direction::Vertical, [Constraint::Percentage(100)]).split(frame.area());
+            Layout::new(Direction::Vertical, constraints).split(frame.area());
         frame.render_widget(self, layout[0]);
+        let mut next_area = layout[1..].iter();
+        if sprint {
+            if let Some(area) = next_area.next() {
+                self.render_sprint_gauge(frame, *area);
+            }
+        }
+        if let (Some(ghost), Some(area)) = (&self.ghost, next_area.next()) {
+            self.render_ghost_gauges(frame, *area, ghost);
+        }
+        if let (Some(multiplayer), Some(area)) = (&self.multiplayer, next_area.next()) {
+            self.render_match_scores(frame, *area, multiplayer);
+        }
         // frame.render_widget(Block::bordered(), layout[1]);
     }
 
+    // live scores of everyone in a networked match
+    fn render_match_scores(&self, frame: &mut Frame, area: Rect, multiplayer: &Multiplayer) {
+        let lines: Vec<Line> = multiplayer
+            .ranking(self.gameconfig.mode)
+            .iter()
+            .map(|p| {
+                let status = if p.finished { " (finished)" } else { "" };
+                let shown = match self.gameconfig.mode {
+                    GameMode::Sprint => p.correct,
+                    _ => p.score,
+                };
+                let line = Line::from(format!("{:<16} {:>4}{}", p.name, shown, status));
+                if p.name == multiplayer.name {
+                    line.cyan().bold()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::bordered()
+                    .title(" Match ")
+                    .border_set(border::DOUBLE),
+            ),
+            area,
+        );
+    }
+
+    // your progress and the ghost's progress towards the same target
+    fn render_ghost_gauges(&self, frame: &mut Frame, area: Rect, ghost: &Ghost) {
+        // the ghost's timeline only has its correct answers, so both sides count those
+        let ghost_score = ghost.score_at(self.get_elapsed_time_ms());
+        let score = self.correct_count();
+        let target = match self.gameconfig.mode {
+            GameMode::Sprint => self.gameconfig.sprint_questions,
+            _ => ghost.final_score.max(score),
+        };
+        let target = target.max(1);
+
+        let block = Block::bordered()
+            .title(" Ghost Race ")
+            .border_set(border::DOUBLE);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        let rows = Layout::new(
+            Direction::Vertical,
+            [Constraint::Length(1), Constraint::Length(1)],
+        )
+        .split(inner);
+
+        let you = LineGauge::default()
+            .filled_style(Style::new().cyan())
+            .label(format!("You   {:>3}", score))
+            .ratio((score as f64 / target as f64).clamp(0.0, 1.0));
+        let ghost_gauge = LineGauge::default()
+            .filled_style(Style::new().dark_gray())
+            .label(format!("Ghost {:>3}", ghost_score))
+            .ratio((ghost_score as f64 / target as f64).clamp(0.0, 1.0));
+        frame.render_widget(you, rows[0]);
+        frame.render_widget(ghost_gauge, rows[1]);
+    }
+
+    // progress through a sprint, shown in place of the elapsed counter
+    fn render_sprint_gauge(&self, frame: &mut Frame, area: Rect) {
+        let target = self.gameconfig.sprint_questions.max(1);
+        let answered = self.correct_count();
+        let gauge = Gauge::default()
+            .block(Block::bordered().border_set(border::DOUBLE))
+            .gauge_style(Style::new().cyan())
+            .ratio((answered as f64 / target as f64).clamp(0.0, 1.0))
+            .label(format!(
+                "{} / {}  ({:.1}s)",
+                answered,
+                target,
+                self.get_elapsed_time_ms() as f64 / 1000.0
+            ));
+        frame.render_widget(gauge, area);
+    }
+
     // /// runs the application's main loop until the user quits
     // pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
     //     let debug_times = crate::util::generate_random_durations(
@@ -211,54 +1185,317 @@ impl MathGame {
     //     Ok(())
     // }
 
+    // quitting part way through a game that can be checkpointed keeps it to resume next time
     pub fn exit(&mut self) {
+        match self.to_checkpoint() {
+            Some(checkpoint) => {
+                let _ = checkpoint.save(CHECKPOINT_PATH);
+            }
+            None => self.handle_game_abandon(),
+        }
         self.exit = true;
     }
+
+    // the game being played as a checkpoint, only plain single player question games are kept
+    pub fn to_checkpoint(&self) -> Option<Checkpoint> {
+        if self.gamestate != GameState::Inprogress
+            || self.multiplayer.is_some()
+            || self.hotseat.is_some()
+            || self.session.is_some()
+            || self.ghost.is_some()
+        {
+            return None;
+        }
+        let now = self.clock_now();
+        Some(Checkpoint {
+            saved_at: Utc::now(),
+            config: self.gameconfig.clone(),
+            seed: self.seed,
+            score: self.score,
+            lives: self.lives,
+            streak: self.streak,
+            best_streak: self.best_streak,
+            combo_score: self.combo_score,
+            elapsed_ms: self.get_elapsed_time_ms(),
+            paused_ms: self.paused_ms,
+            question_ms: (now - self.current_question.question_start).num_milliseconds(),
+            current_question: self.current_question,
+            answers: self.answers.clone(),
+        })
+    }
+
+    // write the game in progress to disk every few seconds
+    pub fn update_checkpoint(&mut self) {
+        let now = Local::now();
+        if (now - self.checkpointed_at).num_milliseconds() < CHECKPOINT_INTERVAL_MS {
+            return;
+        }
+        self.checkpointed_at = now;
+        if let Some(checkpoint) = self.to_checkpoint() {
+            let _ = checkpoint.save(CHECKPOINT_PATH);
+        }
+    }
+
+    // offer to pick up the game left unfinished by the last run
+    pub fn offer_resume(&mut self) {
+        if let Some(checkpoint) = Checkpoint::load(CHECKPOINT_PATH) {
+            self.checkpoint = Some(checkpoint);
+            self.handle_confirm_request(Action::Resume);
+        }
+    }
+
+    // The times table grid over every game played, only rebuilt once the history or the
+    // table settings have changed since it was last drawn
+    pub fn history_mastery(&mut self) -> &MasteryGrid {
+        let games = self.game_history.history.len();
+        let config = &self.gameconfig;
+        let stale = self.history_mastery.as_ref().is_none_or(|(built_from, grid)| {
+            *built_from != games
+                || grid.lower != config.table_lower
+                || grid.upper != config.table_upper.max(config.table_lower)
+                || grid.mastery_ms != config.mastery_ms
+        });
+        if stale {
+            let grid = MasteryGrid::from_history(
+                &self.game_history,
+                config.table_lower,
+                config.table_upper,
+                config.mastery_ms,
+            );
+            self.history_mastery = Some((games, grid));
+        }
+        &self.history_mastery.as_ref().unwrap().1
+    }
+
+    // restore the checkpointed game, declining it abandons the game instead
+    pub fn handle_resume(&mut self, accept: bool) {
+        let Some(checkpoint) = self.checkpoint.take() else {
+            return;
+        };
+        if accept {
+            self.gameconfig = checkpoint.config.clone();
+            self.restore_checkpoint(checkpoint);
+            self.resumed = true;
+            self.gamestate = GameState::Inprogress;
+            return;
+        }
+        Checkpoint::remove(CHECKPOINT_PATH);
+        // the declined game is kept or not as its own config asked, and is recorded under
+        // that config before the player's is put back
+        if checkpoint.config.save_abandoned {
+            let config = std::mem::replace(&mut self.gameconfig, checkpoint.config.clone());
+            self.restore_checkpoint(checkpoint);
+            self.record_game(None, true, true);
+            self.gameconfig = config;
+            self.mastery = None;
+        }
+        self.handle_return_to_splash();
+    }
+
+    // put the game state back as it was checkpointed, under the config already in place
+    fn restore_checkpoint(&mut self, checkpoint: Checkpoint) {
+        let now = Local::now();
+        self.seed = checkpoint.seed;
+        self.rng = StdRng::seed_from_u64(rand::thread_rng().gen());
+        self.score = checkpoint.score;
+        self.lives = checkpoint.lives;
+        self.streak = checkpoint.streak;
+        self.best_streak = checkpoint.best_streak;
+        self.combo_score = checkpoint.combo_score;
+        self.answers = checkpoint.answers;
+        self.current_question = checkpoint.current_question;
+        self.current_question.question_start =
+            now - chrono::Duration::milliseconds(checkpoint.question_ms);
+        self.start_time =
+            now - chrono::Duration::milliseconds(checkpoint.elapsed_ms + checkpoint.paused_ms);
+        self.current_time = now;
+        self.paused_at = None;
+        self.paused_ms = checkpoint.paused_ms;
+        self.held_at = None;
+        self.held_ms = 0;
+        self.flash = None;
+        self.input.clear();
+        self.mastery = (self.gameconfig.mode == GameMode::Mastery).then(|| {
+            let mut grid = MasteryGrid::from_history(
+                &self.game_history,
+                self.gameconfig.table_lower,
+                self.gameconfig.table_upper,
+                self.gameconfig.mastery_ms,
+            );
+            self.answers.iter().for_each(|a| grid.record(a));
+            grid
+        });
+    }
 }
 
 impl Widget for &MathGame {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let score = Title::from(Line::from(vec![
-            " Score:  ".into(),
-            self.score.to_string().bold(),
-            "  Elapsed:  ".into(),
-            self.get_elapsed_time_seconds().to_string().bold(),
-            " ".into(),
-        ]));
-
-            let instructions = Title::from(Line::from(vec![
-                " Reset ".into(),
-                "<R>".blue().bold(),
-                " Quit ".into(),
-                "<Q>".blue().bold(),
-                " Return to Start ".into(),
-                "<D> ".blue().bold(),
-                " End ".into(),
-                "<E> ".blue().bold(),
-            ]));
+        let mut score_spans = vec![];
+        if let Some(session) = &self.session {
+            if let Some(section) = session.current_section() {
+                score_spans.push(
+                    format!(
+                        " {} {}/{}: {} ",
+                        session.name,
+                        session.current + 1,
+                        session.sections.len(),
+                        section.name
+                    )
+                    .cyan()
+                    .bold(),
+                );
+            }
+        }
+        if let Some(hotseat) = &self.hotseat {
+            score_spans.push(format!(" Player {} ", hotseat.player + 1).cyan().bold());
+        }
+        score_spans.push(" Score:  ".into());
+        score_spans.push(self.score.to_string().bold());
+        if self.gameconfig.mode == GameMode::Survival {
+            score_spans.push("  Lives:  ".into());
+            score_spans.push(
+                format!(
+                    "{}{}",
+                    "♥".repeat(self.lives.max(0) as usize),
+                    "♡".repeat((self.gameconfig.lives - self.lives).max(0) as usize)
+                )
+                .red()
+                .bold(),
+            );
+        }
+        if self.gameconfig.estimation {
+            score_spans.push("  Points:  ".into());
+            score_spans.push(util::estimate_points(&self.answers).to_string().bold());
+        }
+        if self.gameconfig.combo_scoring {
+            score_spans.push("  Combo:  ".into());
+            score_spans.push(self.combo_score.to_string().bold());
+            score_spans.push("  Streak:  ".into());
+            let streak = format!("{} x{}", self.streak, self.combo_multiplier());
+            score_spans.push(match self.streak {
+                0 => streak.bold(),
+                _ => streak.magenta().bold(),
+            });
+        }
+        if let Some(mastery) = &self.mastery {
+            score_spans.push("  Mastered:  ".into());
+            score_spans.push(
+                format!("{}/{}", mastery.mastered_count(), mastery.facts().len())
+                    .green()
+                    .bold(),
+            );
+        }
+        if let Some(ghost) = &self.ghost {
+            let ghost_score = ghost.score_at(self.get_elapsed_time_ms());
+            let delta = self.correct_count() - ghost_score;
+            score_spans.push("  Ghost:  ".into());
+            score_spans.push(ghost_score.to_string().bold());
+            score_spans.push(" ".into());
+            score_spans.push(match delta {
+                d if d > 0 => format!("(+{})", d).green().bold(),
+                d if d < 0 => format!("({})", d).red().bold(),
+                _ => "(=)".yellow().bold(),
+            });
+        }
+        if self.gameconfig.mode != GameMode::Sprint {
+            score_spans.push("  Elapsed:  ".into());
+            score_spans.push(self.get_elapsed_time_seconds().to_string().bold());
+        }
+        score_spans.push(" ".into());
+        let score = Title::from(Line::from(score_spans));
+
+        // multiple choice claims 1-5 for picking options, so keys bound to those are not listed
+        let actions = [
+            Action::Restart,
+            Action::Quit,
+            Action::ReturnToStart,
+            Action::EndGame,
+            Action::Skip,
+            Action::Pause,
+        ]
+        .into_iter()
+        // a networked match can't be paused
+        .filter(|a| *a != Action::Pause || self.multiplayer.is_none())
+        .collect::<Vec<Action>>();
+        let instructions = if self.gameconfig.multiple_choice {
+            let actions: Vec<Action> = actions
+                .into_iter()
+                .filter(|a| {
+                    self.keymap
+                        .key(Screen::Game, *a)
+                        .is_some_and(|k| !"12345".contains(k))
+                })
+                .collect();
+            let mut spans = vec![" Choose ".into(), "<1-5>".blue().bold()];
+            spans.append(&mut self.keymap.spans(Screen::Game, &actions));
+            Title::from(Line::from(spans))
+        } else {
+            let mut spans = vec![" Submit ".into(), "<Enter>".blue().bold()];
+            spans.append(&mut self.keymap.spans(Screen::Game, &actions));
+            Title::from(Line::from(spans))
+        };
 
         let block: Block<'_> = Block::bordered()
             .title(score.alignment(Alignment::Center).position(Position::Top))
             .title(instructions.alignment(Alignment::Center).position(Position::Bottom))
 
             .border_set(border::DOUBLE);
-        let input_line = Span::from(self.input.clone().white());
-
-        let counter_text = Text::from(vec![
-            Line::from(vec![format!("Question {}: ", self.score + 1).yellow()]),
-            Line::from(vec![
-                self.current_question.lhs.to_string().into(),
-                " ".into(),
-                util::match_sign(&self.current_question.sign)
-                    .to_string()
-                    .into(),
-                " ".into(),
-                self.current_question.rhs.to_string().into(),
-                " = ".into(),
-                // self.current_question.answer.to_string().into(),
-                input_line,
-            ]),
+        let input_spans = self.input.spans(Style::new().white());
+
+        if self.paused_at.is_some() {
+            let paused_text = Text::from(vec![
+                Line::from(vec!["Paused".yellow().bold()]),
+                Line::from(vec![
+                    " Resume ".into(),
+                    format!("<{}>", self.keymap.key(Screen::Game, Action::Pause).unwrap_or('p'))
+                        .to_uppercase()
+                        .blue()
+                        .bold(),
+                ]),
+            ]);
+            Paragraph::new(paused_text)
+                .alignment(Alignment::Center)
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        if let Some(flashed) = self.flash {
+            let flash_text = Text::from(vec![
+                Line::from(vec!["Time's up!".red().bold()]),
+                Line::from(vec![
+                    flashed.prompt().into(),
+                    flashed.answer_label().red().bold(),
+                ]),
+            ]);
+            Paragraph::new(flash_text)
+                .alignment(Alignment::Center)
+                .block(block)
+                .render(area, buf);
+            return;
+        }
+
+        let mut question_header = vec![format!("Question {}: ", self.answers.len() + 1).yellow()];
+        if let Some(limit) = self.question_limit() {
+            let remaining = (limit as i64 - self.current_question.elapsed_seconds()).max(0);
+            question_header.push(format!("({}s)", remaining).dark_gray());
+        }
+
+        let mut counter_text = Text::from(vec![
+            Line::from(question_header),
+            Line::from(
+                [vec![self.current_question.prompt().into()], input_spans].concat(),
+            ),
         ]);
+        if let Some(options) = self.current_question.options {
+            let mut option_spans = vec![];
+            for (number, option) in "12345".chars().zip(options) {
+                option_spans.push(format!("{})", number).blue().bold());
+                option_spans.push(format!(" {}   ", option).into());
+            }
+            counter_text.push_line(Line::from(""));
+            counter_text.push_line(Line::from(option_spans));
+        }
 
         Paragraph::new(counter_text)
             .alignment(Alignment::Center)
@@ -271,30 +1508,187 @@ impl Widget for &MathGame {
 impl MathQuestion {
 
     //generates a math answer, used to stop recomputing each UI tick
-    fn generate_math_answer(self) -> MathAnswer {
-        let srep = format!(
-            "{:<3} {} {:<3}",
-            self.lhs.to_string(),
-            self.sign.to_string(),
-            self.rhs.to_string()
-        );
+    fn generate_math_answer(self, outcome: AnswerOutcome) -> MathAnswer {
+        let srep = self.question_label();
         let duration_s =
             (self.question_answer.unwrap_or(Local::now()) - self.question_start).num_seconds();
         let duration_m =
             (self.question_answer.unwrap_or(Local::now()) - self.question_start).num_milliseconds();
-        return MathAnswer {
+        MathAnswer {
             q: self,
             string_representation: srep,
             duration_s,
             duration_m,
-        };
+            outcome,
+            error_factor: None,
+            elapsed_m: None,
+        }
     }
 
-    //generates the LHS and RHS values for a question given a question range
-    fn generate_lhs_rhs(qr: &QuestionRanges, sign: &Sign) -> (i32, i32) {
-        let mut rng = rand::thread_rng();
+    // check a submitted answer string against the question, estimates are never exact
+    pub fn is_correct(&self, input: &str) -> bool {
+        match self.sign {
+            _ if self.estimate.is_some() => false,
+            Sign::Prime => {
+                let yes = input.trim().eq_ignore_ascii_case("y");
+                let no = input.trim().eq_ignore_ascii_case("n");
+                (yes && self.answer == 1) || (no && self.answer == 0)
+            }
+            Sign::Factorise => util::parse_factorisation(input) == Some(self.answer),
+            Sign::Divide if self.division.is_some() => self.is_correct_division(input),
+            _ => input.trim().parse::<i32>() == Ok(self.answer),
+        }
+    }
+
+    // The quotient in units of the last decimal place, halves round away from zero. Both
+    // the checker and the answer label go through this so 9/8 is 1.13 in each
+    fn rounded_quotient(&self, places: u32) -> f64 {
+        (self.lhs as f64 / self.rhs as f64 * 10f64.powi(places as i32)).round()
+    }
+
+    // check a division with a remainder in whichever format it was asked in
+    fn is_correct_division(&self, input: &str) -> bool {
+        let input = input.trim();
+        match self.division {
+            Some(DivisionFormat::Remainder) => {
+                let Some((quotient, remainder)) = input.split_once('r') else {
+                    return false;
+                };
+                quotient.trim().parse::<i32>() == Ok(self.lhs / self.rhs)
+                    && remainder.trim().parse::<i32>() == Ok(self.lhs % self.rhs)
+            }
+            // any more decimal places than asked for is not an answer yet
+            Some(DivisionFormat::Decimal(places)) => {
+                let typed_places = input.split_once('.').map_or(0, |(_, d)| d.len());
+                let scale = 10f64.powi(places as i32);
+                let expected = self.rounded_quotient(places);
+                typed_places <= places as usize
+                    && input
+                        .parse::<f64>()
+                        .is_ok_and(|value| (value * scale).round() == expected)
+            }
+            // equivalent fractions are accepted, 3 4/10 is as good as 3 2/5
+            Some(DivisionFormat::MixedFraction) => {
+                let (whole, fraction) = input.split_once(' ').unwrap_or(("0", input));
+                let Some((numerator, denominator)) = fraction.split_once('/') else {
+                    return false;
+                };
+                let (Ok(whole), Ok(numerator), Ok(denominator)) = (
+                    whole.trim().parse::<i64>(),
+                    numerator.trim().parse::<i64>(),
+                    denominator.trim().parse::<i64>(),
+                ) else {
+                    return false;
+                };
+                denominator > 0
+                    && numerator < denominator
+                    && (whole * denominator + numerator) * self.rhs as i64
+                        == self.lhs as i64 * denominator
+            }
+            _ => input.parse::<i32>() == Ok(self.answer),
+        }
+    }
+
+    // which characters can be typed as this question's answer
+    pub fn input_kind(&self) -> InputKind {
+        match (self.sign, self.division) {
+            _ if self.estimate.is_some() => InputKind::Scientific,
+            (Sign::Prime, _) => InputKind::YesNo,
+            (Sign::Factorise, _) => InputKind::Factorisation,
+            (Sign::Divide, Some(DivisionFormat::Remainder)) => InputKind::Remainder,
+            (Sign::Divide, Some(DivisionFormat::Decimal(_))) => InputKind::Decimal,
+            (Sign::Divide, Some(DivisionFormat::MixedFraction)) => InputKind::Fraction,
+            _ => InputKind::Integer,
+        }
+    }
+
+    // the question as put to the player, up to where the answer is typed
+    pub fn prompt(&self) -> String {
+        match self.sign {
+            Sign::Prime => format!("is {} prime? (y/n) ", self.lhs),
+            Sign::Factorise => format!("factorise {} = ", self.lhs),
+            Sign::Gcd | Sign::Lcm => format!("{}({}, {}) = ", self.sign, self.lhs, self.rhs),
+            _ => format!(
+                "{} {} {} = ",
+                self.lhs_label(),
+                util::match_sign(&self.sign),
+                self.rhs_label()
+            ),
+        }
+    }
+
+    // the question as shown in tables, number theory questions read as words
+    pub fn question_label(&self) -> String {
+        match self.sign {
+            Sign::Prime => format!("{} prime?", self.lhs),
+            Sign::Factorise => format!("factor {}", self.lhs),
+            Sign::Gcd | Sign::Lcm => format!("{}({}, {})", self.sign, self.lhs, self.rhs),
+            _ => format!("{:<3} {} {:<3}", self.lhs_label(), self.sign, self.rhs_label()),
+        }
+    }
+
+    // ratio between an estimate and the true answer, always at least one
+    pub fn error_factor(&self, input: &str) -> Option<f64> {
+        let estimate = self.estimate?;
+        let guess: f64 = input.trim().parse().ok()?;
+        if guess <= 0.0 {
+            return None;
+        }
+        Some((guess / estimate.answer).max(estimate.answer / guess))
+    }
+
+    pub fn lhs_label(&self) -> String {
+        match self.estimate {
+            Some(estimate) => format!("{:.1e}", estimate.lhs),
+            None => self.lhs.to_string(),
+        }
+    }
+
+    pub fn rhs_label(&self) -> String {
+        match self.estimate {
+            Some(estimate) => format!("{:.1e}", estimate.rhs),
+            None => self.rhs.to_string(),
+        }
+    }
+
+    pub fn answer_label(&self) -> String {
+        match (self.estimate, self.sign) {
+            (Some(estimate), _) => format!("{:.2e}", estimate.answer),
+            (None, Sign::Prime) if self.answer == 1 => "y".to_string(),
+            (None, Sign::Prime) => "n".to_string(),
+            (None, Sign::Factorise) => util::factorisation(self.answer),
+            (None, Sign::Divide) => match self.division {
+                Some(DivisionFormat::Remainder) => {
+                    format!("{} r {}", self.lhs / self.rhs, self.lhs % self.rhs)
+                }
+                Some(DivisionFormat::Decimal(places)) => {
+                    let scale = 10f64.powi(places as i32);
+                    format!("{:.*}", places as usize, self.rounded_quotient(places) / scale)
+                }
+                Some(DivisionFormat::MixedFraction) => {
+                    let remainder = self.lhs % self.rhs;
+                    let common = util::gcd(remainder, self.rhs);
+                    format!(
+                        "{} {}/{}",
+                        self.lhs / self.rhs,
+                        remainder / common,
+                        self.rhs / common
+                    )
+                }
+                _ => self.answer.to_string(),
+            },
+            (None, _) => self.answer.to_string(),
+        }
+    }
 
-        return match sign {
+    // seconds since this question was put on screen
+    pub fn elapsed_seconds(&self) -> i64 {
+        (Local::now() - self.question_start).num_seconds()
+    }
+
+    //generates the LHS and RHS values for a question given a question range
+    fn generate_lhs_rhs(qr: &QuestionRanges, sign: &Sign, rng: &mut StdRng) -> (i32, i32) {
+        match sign {
             Sign::Multiply => (
                 rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
                 rng.gen_range(qr.mult_rhs_lower..qr.mult_rhs_upper),
@@ -319,30 +1713,277 @@ impl MathQuestion {
                 let ans = lhs * rhs;
                 (ans, lhs)
             }
-        };
+            // half the numbers asked about are prime, the rest are odd composites and evens
+            Sign::Prime => {
+                let range = qr.add_lower..qr.add_upper;
+                let primes: Vec<i32> = range.clone().filter(|n| util::is_prime(*n)).collect();
+                match primes.choose(rng) {
+                    Some(prime) if rng.gen_bool(0.5) => (*prime, 0),
+                    _ => (rng.gen_range(range), 0),
+                }
+            }
+            Sign::Factorise => {
+                let count = rng.gen_range(2..=4);
+                let n = (0..count)
+                    .map(|_| *[2, 3, 5, 7, 11, 13].choose(rng).unwrap_or(&2))
+                    .product();
+                (n, 0)
+            }
+            // share a common factor so the answer is more than one
+            Sign::Gcd => {
+                let common = rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper);
+                (
+                    common * rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
+                    common * rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
+                )
+            }
+            Sign::Lcm => (
+                rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
+                rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
+            ),
+            Sign::Mod => (
+                rng.gen_range(qr.mult_rhs_lower..qr.mult_rhs_upper * 10),
+                rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper),
+            ),
+        }
     }
 
     //randomly generate a new question
-    pub fn generate_new_question(qr: &QuestionRanges) -> MathQuestion {
-        let sign = match rand::thread_rng().gen_range(1..5) {
-            1 => Sign::Add,
-            2 => Sign::Subtract,
-            3 => Sign::Multiply,
-            4 => Sign::Divide,
-            _ => panic!("Shouldn't be able to generate this"),
-        };
+    pub fn generate_new_question(
+        qr: &QuestionRanges,
+        signs: &[Sign],
+        rng: &mut StdRng,
+    ) -> MathQuestion {
+        let sign = *signs.choose(rng).unwrap_or(&Sign::Add);
 
-        let lhs_rhs = Self::generate_lhs_rhs(qr, &sign);
+        let lhs_rhs = Self::generate_lhs_rhs(qr, &sign, rng);
         let answer: i32 = util::apply_sign(&sign, lhs_rhs.0, lhs_rhs.1);
         
-        let new_question = MathQuestion {
+        MathQuestion {
             lhs: lhs_rhs.0,
             rhs: lhs_rhs.1,
             answer,
             sign,
             question_start: Local::now(),
             question_answer: Option::None,
+            options: None,
+            estimate: None,
+            division: None,
+        }
+    }
+
+    // a times table fact, asked either way round
+    pub fn generate_fact_question(a: i32, b: i32, rng: &mut StdRng) -> MathQuestion {
+        let (lhs, rhs) = if rng.gen_bool(0.5) { (a, b) } else { (b, a) };
+        MathQuestion {
+            lhs,
+            rhs,
+            answer: lhs * rhs,
+            sign: Sign::Multiply,
+            question_start: Local::now(),
+            question_answer: Option::None,
+            options: None,
+            estimate: None,
+            division: None,
+        }
+    }
+
+    // a product or quotient of numbers spanning several orders of magnitude
+    pub fn generate_estimate_question(signs: &[Sign], rng: &mut StdRng) -> MathQuestion {
+        let products: Vec<Sign> = signs
+            .iter()
+            .copied()
+            .filter(|s| *s == Sign::Multiply || *s == Sign::Divide)
+            .collect();
+        let sign = *products.choose(rng).unwrap_or(&Sign::Multiply);
+        let mut operand = || rng.gen_range(10..100) as f64 / 10.0 * 10f64.powi(rng.gen_range(1..7));
+        let lhs = operand();
+        let rhs = operand();
+        let answer = match sign {
+            Sign::Divide => lhs / rhs,
+            _ => lhs * rhs,
         };
-        return new_question;
+        MathQuestion {
+            lhs: 0,
+            rhs: 0,
+            answer: 0,
+            sign,
+            question_start: Local::now(),
+            question_answer: Option::None,
+            options: None,
+            estimate: Some(Estimate { lhs, rhs, answer }),
+            division: None,
+        }
+    }
+
+    // continue a chain, the previous answer becomes the new left operand
+    pub fn generate_chained_question(
+        previous: &MathQuestion,
+        qr: &QuestionRanges,
+        signs: &[Sign],
+        rng: &mut StdRng,
+    ) -> MathQuestion {
+        let lhs = previous.answer;
+        let mut shuffled = signs.to_vec();
+        shuffled.shuffle(rng);
+
+        for sign in shuffled {
+            // pick an rhs that keeps the running total within the chain bounds
+            let rhs = match sign {
+                Sign::Add => Some(rng.gen_range(qr.add_lower..qr.add_upper))
+                    .filter(|rhs| lhs + rhs <= qr.chain_upper),
+                Sign::Subtract => Some(rng.gen_range(qr.add_lower..qr.add_upper))
+                    .filter(|rhs| lhs - rhs >= qr.add_lower),
+                Sign::Multiply => Some(rng.gen_range(qr.mult_lhs_lower..qr.mult_lhs_upper))
+                    .filter(|rhs| lhs * rhs <= qr.chain_upper),
+                Sign::Divide => {
+                    let divisors: Vec<i32> = (qr.mult_lhs_lower..qr.mult_lhs_upper)
+                        .filter(|d| lhs % d == 0 && lhs / d >= qr.add_lower)
+                        .collect();
+                    divisors.choose(rng).copied()
+                }
+                // number theory questions do not continue a running total
+                Sign::Prime | Sign::Factorise | Sign::Gcd | Sign::Lcm | Sign::Mod => None,
+            };
+            if let Some(rhs) = rhs {
+                return MathQuestion {
+                    lhs,
+                    rhs,
+                    answer: util::apply_sign(&sign, lhs, rhs),
+                    sign,
+                    question_start: Local::now(),
+                    question_answer: Option::None,
+                    options: None,
+                    estimate: None,
+                    division: None,
+                };
+            }
+        }
+
+        // nothing fits the bounds, so start a new chain
+        Self::generate_new_question(qr, signs, rng)
+    }
+
+    // leave a remainder on a division so it is no longer exact
+    pub fn with_remainder(mut self, format: DivisionFormat, rng: &mut StdRng) -> MathQuestion {
+        if self.sign != Sign::Divide || self.rhs < 2 {
+            return self;
+        }
+        self.lhs += rng.gen_range(1..self.rhs);
+        self.answer = self.lhs / self.rhs;
+        self.division = Some(format);
+        self
+    }
+
+    // add four plausible wrong answers alongside the real one, in a random position
+    pub fn with_options(mut self, rng: &mut StdRng) -> MathQuestion {
+        // yes or no, factorisation and remainder answers are always typed
+        if self.sign == Sign::Prime || self.sign == Sign::Factorise || self.division.is_some() {
+            return self;
+        }
+        let answer = self.answer;
+        let mut candidates = vec![
+            answer + 1,
+            answer - 1,
+            answer + 2,
+            answer - 2,
+            answer + 10,
+            answer - 10,
+        ];
+        // slips that come from misapplying an operand
+        match self.sign {
+            Sign::Multiply => candidates.extend([answer + self.lhs, answer - self.lhs]),
+            Sign::Divide => {
+                candidates.extend([self.lhs / (self.rhs - 1).max(1), self.lhs / (self.rhs + 1)])
+            }
+            Sign::Add | Sign::Subtract => candidates.extend([answer + 100, answer - 100]),
+            Sign::Gcd | Sign::Lcm => candidates.extend([self.lhs, self.rhs, self.lhs * self.rhs]),
+            Sign::Mod => candidates.extend([self.rhs - answer, self.lhs / self.rhs]),
+            Sign::Prime | Sign::Factorise => {}
+        }
+        // transposed last two digits
+        if answer >= 10 {
+            let tens = (answer / 10) % 10;
+            let units = answer % 10;
+            candidates.push(answer - tens * 10 - units + units * 10 + tens);
+        }
+
+        candidates.retain(|c| *c != answer && *c >= 0);
+        candidates.sort();
+        candidates.dedup();
+        candidates.shuffle(rng);
+        // small answers may not have four distinct distractors yet
+        let mut next = answer + 3;
+        while candidates.len() < 4 {
+            if !candidates.contains(&next) {
+                candidates.push(next);
+            }
+            next += 1;
+        }
+
+        let mut options = [answer; 5];
+        let correct = rng.gen_range(0..5);
+        let mut distractors = candidates.into_iter();
+        for (i, option) in options.iter_mut().enumerate() {
+            if i != correct {
+                *option = distractors.next().unwrap_or(answer);
+            }
+        }
+        self.options = Some(options);
+        self
+    }
+
+    // the option picked with key 1-5, None if the key is not an option
+    pub fn option_for_key(&self, key: char) -> Option<i32> {
+        let index = "12345".find(key)?;
+        self.options.map(|options| options[index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn division(lhs: i32, rhs: i32, format: DivisionFormat) -> MathQuestion {
+        MathQuestion {
+            lhs,
+            rhs,
+            answer: lhs / rhs,
+            sign: Sign::Divide,
+            question_start: Local::now(),
+            question_answer: None,
+            options: None,
+            estimate: None,
+            division: Some(format),
+        }
+    }
+
+    #[test]
+    fn remainders_need_both_parts() {
+        let question = division(17, 5, DivisionFormat::Remainder);
+        assert!(question.is_correct_division("3r2"));
+        assert!(question.is_correct_division(" 3 r 2 "));
+        assert!(!question.is_correct_division("3r3"));
+        assert!(!question.is_correct_division("3"));
+    }
+
+    #[test]
+    fn decimals_round_half_away_from_zero_to_the_places_asked() {
+        let question = division(9, 8, DivisionFormat::Decimal(2));
+        assert!(question.is_correct_division("1.13"));
+        assert!(!question.is_correct_division("1.12"));
+        assert!(!question.is_correct_division("1.125"));
+        assert!(division(8, 4, DivisionFormat::Decimal(1)).is_correct_division("2"));
+    }
+
+    #[test]
+    fn mixed_fractions_accept_equivalent_fractions() {
+        let question = division(17, 5, DivisionFormat::MixedFraction);
+        assert!(question.is_correct_division("3 2/5"));
+        assert!(question.is_correct_division("3 4/10"));
+        assert!(!question.is_correct_division("3 5/5"));
+        assert!(!question.is_correct_division("17/5"));
+        assert!(!question.is_correct_division("3 2/0"));
+        assert!(division(2, 5, DivisionFormat::MixedFraction).is_correct_division("2/5"));
     }
 }
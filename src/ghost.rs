@@ -0,0 +1,43 @@
+use crate::{config::GameConfiguration, game::AnswerOutcome, history::GameRecord};
+
+// a previous game replayed alongside the current one
+#[derive(Debug, Clone)]
+pub struct Ghost {
+    // elapsed game time in milliseconds at which each correct answer was given
+    pub score_times_ms: Vec<i64>,
+    pub final_score: i32,
+    // seed of the raced game, replaying it gives the same questions
+    pub seed: Option<u64>,
+    // the raced game, the result of the race is decided the same way as the leaderboards
+    pub record: GameRecord,
+    // config to restore once the race is over
+    pub base_config: GameConfiguration,
+}
+
+impl Ghost {
+    // Build the ghost's timeline from the game clock kept on each answer, which already leaves
+    // out pauses and counts timeout flashes and skip penalties. Older records only have answer
+    // durations, which are summed instead
+    pub fn from_record(record: &GameRecord, base_config: GameConfiguration) -> Ghost {
+        let mut elapsed = 0;
+        let mut score_times_ms = vec![];
+        for answer in record.answers.iter() {
+            elapsed = answer.elapsed_m.unwrap_or(elapsed + answer.duration_m);
+            if answer.outcome == AnswerOutcome::Correct {
+                score_times_ms.push(elapsed);
+            }
+        }
+        Ghost {
+            final_score: score_times_ms.len() as i32,
+            score_times_ms,
+            seed: record.seed,
+            record: record.clone(),
+            base_config,
+        }
+    }
+
+    // the ghost's score at the same point in its game
+    pub fn score_at(&self, elapsed_ms: i64) -> i32 {
+        self.score_times_ms.partition_point(|t| *t <= elapsed_ms) as i32
+    }
+}
@@ -5,27 +5,167 @@ use std::fs::File;
 use std::io::{self, Read, Write};
 use std::path::Path;
 
-use crate::MathAnswer;
+use crate::{
+    config::{EndSettings, GameMode, QuestionSettings},
+    flash::FlashRound,
+    game::AnswerOutcome,
+    market::MarketRound,
+    span::SpanTrial,
+    MathAnswer,
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct GameHistory {
     path: String,
     pub history: Vec<GameRecord>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GameRecord {
     #[serde(with = "ts_nanoseconds")]
     pub game_intant: DateTime<Utc>,
     pub score: i32,
+    #[serde(default)]
+    pub mode: GameMode,
+    // total game length in milliseconds, the score for sprint games
+    #[serde(default)]
+    pub duration_m: i64,
+    // questions a sprint had to answer, only set for sprint games
+    #[serde(default)]
+    pub sprint_questions: Option<i32>,
+    // paused games are kept in history but left off the leaderboards
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub paused_ms: i64,
+    // seed the questions were generated from, replaying it gives the same sequence
+    #[serde(default)]
+    pub seed: Option<u64>,
+    // what the questions were generated from, missing for records saved before it was kept
+    #[serde(default)]
+    pub question_settings: Option<QuestionSettings>,
+    // when the game was set to end, missing for records saved before it was kept
+    #[serde(default)]
+    pub end_settings: Option<EndSettings>,
+    // id of the networked match this game was part of
+    #[serde(default)]
+    pub match_id: Option<String>,
+    // score with combo multipliers applied, only for games played with combo scoring
+    #[serde(default)]
+    pub combo_score: Option<i32>,
+    #[serde(default)]
+    pub best_streak: i32,
+    // links the sections of a session such as a mock test
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub section: Option<String>,
+    // every round of a flash-sum game, empty for other modes
+    #[serde(default)]
+    pub flash_rounds: Vec<FlashRound>,
+    // every trial of a digit span game and the longest number recalled
+    #[serde(default)]
+    pub span_trials: Vec<SpanTrial>,
+    #[serde(default)]
+    pub max_span: Option<usize>,
+    // every quote of a market making game and the hidden sum they settled at
+    #[serde(default)]
+    pub market_rounds: Vec<MarketRound>,
+    #[serde(default)]
+    pub market_value: Option<i32>,
+    // left by restarting, returning to start or quitting part way through
+    #[serde(default)]
+    pub abandoned: bool,
+    // picked up from a checkpoint after a quit or crash
+    #[serde(default)]
+    pub resumed: bool,
     pub answers: Vec<MathAnswer>,
 }
 
-impl Default for GameHistory {
-    fn default() -> Self {
-        Self {
-            path: Default::default(),
-            history: Default::default(),
+impl GameRecord {
+    // mode shown in the history table, session sections show their section name
+    pub fn mode_label(&self) -> String {
+        match &self.section {
+            Some(section) => section.clone(),
+            None => self.mode.name().to_string(),
+        }
+    }
+
+    pub fn count(&self, outcome: AnswerOutcome) -> usize {
+        self.answers.iter().filter(|a| a.outcome == outcome).count()
+    }
+
+    // mean time in milliseconds of the correct answers
+    pub fn average_correct_ms(&self) -> Option<i64> {
+        let correct: Vec<i64> = self
+            .answers
+            .iter()
+            .filter(|a| a.outcome == AnswerOutcome::Correct)
+            .map(|a| a.duration_m)
+            .collect();
+        match correct.len() {
+            0 => None,
+            n => Some(correct.iter().sum::<i64>() / n as i64),
+        }
+    }
+
+    // order two records by result, Greater means self is the better game
+    pub fn compare(&self, other: &GameRecord) -> std::cmp::Ordering {
+        match self.mode {
+            // unfinished sprints rank below finished ones, then the fastest wins
+            GameMode::Sprint => self
+                .is_finished_sprint()
+                .cmp(&other.is_finished_sprint())
+                .then(
+                    self.count(AnswerOutcome::Correct)
+                        .cmp(&other.count(AnswerOutcome::Correct)),
+                )
+                .then(other.duration_m.cmp(&self.duration_m)),
+            _ => self.score.cmp(&other.score),
+        }
+    }
+
+    // Whether a sprint answered all its questions rather than being ended early. Sprints
+    // saved before the target was kept are assumed to have finished
+    pub fn is_finished_sprint(&self) -> bool {
+        self.mode == GameMode::Sprint
+            && self
+                .sprint_questions
+                .is_none_or(|n| self.count(AnswerOutcome::Correct) >= n as usize)
+    }
+
+    // sprints of different lengths are never compared, older ones without a length always are
+    pub fn is_sprint_of(&self, questions: i32) -> bool {
+        self.sprint_questions.is_none_or(|n| n == questions)
+    }
+
+    // the result to show for this record, finished sprints are scored by time
+    pub fn score_label(&self) -> String {
+        match (self.mode, self.sprint_questions) {
+            (GameMode::Sprint, Some(n)) if !self.is_finished_sprint() => {
+                format!("{}/{}", self.count(AnswerOutcome::Correct), n)
+            }
+            (GameMode::Sprint, _) => format!("{:.1}s", self.duration_m as f64 / 1000.0),
+            _ => self.score.to_string(),
+        }
+    }
+}
+
+// Records saved before answers had outcomes held every correct answer followed by the
+// question left on screen when time ran out, so that last one is marked unanswered
+fn migrate_outcomes(value: &mut serde_json::Value) {
+    let Some(records) = value.get_mut("history").and_then(|h| h.as_array_mut()) else {
+        return;
+    };
+    for record in records {
+        let Some(answers) = record.get_mut("answers").and_then(|a| a.as_array_mut()) else {
+            continue;
+        };
+        if answers.iter().any(|a| a.get("outcome").is_some()) {
+            continue;
+        }
+        if let Some(last) = answers.last_mut().and_then(|a| a.as_object_mut()) {
+            last.insert("outcome".to_string(), "Unanswered".into());
         }
     }
 }
@@ -42,7 +182,9 @@ impl GameHistory {
             file.read_to_string(&mut contents)?;
 
             // Deserialize the JSON content into a GameHistory struct
-            let mut history: GameHistory = serde_json::from_str(&contents)?;
+            let mut value: serde_json::Value = serde_json::from_str(&contents)?;
+            migrate_outcomes(&mut value);
+            let mut history: GameHistory = serde_json::from_value(value)?;
             history.path = path_str; // Update the path
 
             Ok(history)
@@ -65,6 +207,49 @@ impl GameHistory {
         self.history.push(result);
     }
 
+    // All records played to the end in a game mode, oldest first
+    pub fn records_for_mode(&self, mode: GameMode) -> Vec<&GameRecord> {
+        self.history
+            .iter()
+            .filter(|r| r.mode == mode && !r.abandoned)
+            .collect()
+    }
+
+    // Best results for a game mode, highest score or most complete and fastest sprint first.
+    // Sprints are only ranked against others of the same length
+    pub fn high_scores(
+        &self,
+        mode: GameMode,
+        sprint_questions: i32,
+        count: usize,
+    ) -> Vec<&GameRecord> {
+        let mut records = self.records_for_mode(mode);
+        // session sections use their own configs so are not comparable
+        records.retain(|r| {
+            !r.paused && r.session_id.is_none() && r.is_sprint_of(sprint_questions)
+        });
+        records.sort_by(|a, b| b.compare(a));
+        records.truncate(count);
+        records
+    }
+
+    // Longest digit span recalled in each digit span game, oldest first
+    pub fn max_span_history(&self) -> Vec<(DateTime<Utc>, usize)> {
+        self.history
+            .iter()
+            .filter(|r| !r.abandoned)
+            .filter_map(|r| r.max_span.map(|span| (r.game_intant, span)))
+            .collect()
+    }
+
+    // Records of every section played in a session, in order
+    pub fn session_records(&self, session_id: &str) -> Vec<&GameRecord> {
+        self.history
+            .iter()
+            .filter(|r| r.session_id.as_deref() == Some(session_id))
+            .collect()
+    }
+
     // Save the game history to a file
     pub fn save(&self) -> io::Result<()> {
         let json = serde_json::to_string(self)?;
@@ -72,3 +257,36 @@ impl GameHistory {
         file.write_all(json.as_bytes())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn legacy_records_mark_their_last_answer_unanswered() {
+        let mut value = json!({
+            "path": "results.json",
+            "history": [
+                {"score": 2, "answers": [{"duration_m": 1}, {"duration_m": 2}, {"duration_m": 3}]},
+                {"score": 1, "answers": [{"outcome": "Wrong"}, {"outcome": "Correct"}]},
+                {"score": 0, "answers": []}
+            ]
+        });
+        migrate_outcomes(&mut value);
+        let outcomes = |record: usize| -> Vec<Option<String>> {
+            value["history"][record]["answers"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a.get("outcome").and_then(|o| o.as_str()).map(String::from))
+                .collect()
+        };
+        assert_eq!(outcomes(0), vec![None, None, Some("Unanswered".to_string())]);
+        assert_eq!(
+            outcomes(1),
+            vec![Some("Wrong".to_string()), Some("Correct".to_string())]
+        );
+        assert!(outcomes(2).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+use std::ops::Deref;
+
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    style::{Style, Stylize},
+    text::Span,
+};
+
+// characters an answer can be made of, set by the type of question being answered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputKind {
+    Digits,
+    Integer,
+    Decimal,
+    // e notation, like 3.7e5
+    Scientific,
+    // quotient and remainder, like 3 r 2
+    Remainder,
+    // mixed fraction, like 3 2/5
+    Fraction,
+    // prime factors, like 2^3*3*7
+    Factorisation,
+    YesNo,
+    // a bid and an ask
+    Quote,
+}
+
+impl InputKind {
+    pub fn allows(&self, c: char) -> bool {
+        if c.is_ascii_digit() {
+            return *self != InputKind::YesNo;
+        }
+        match self {
+            InputKind::Digits => false,
+            InputKind::Integer => c == '-',
+            InputKind::Decimal => c == '-' || c == '.',
+            InputKind::Scientific => matches!(c, '-' | '+' | '.' | 'e' | 'E'),
+            InputKind::Remainder => c == ' ' || c == 'r',
+            InputKind::Fraction => c == ' ' || c == '/',
+            InputKind::Factorisation => c == '^' || c == '*',
+            InputKind::YesNo => matches!(c, 'y' | 'n' | 'Y' | 'N'),
+            InputKind::Quote => c == ' ',
+        }
+    }
+}
+
+// single line answer editor with a cursor
+#[derive(Debug, Clone, Default)]
+pub struct InputEditor {
+    text: String,
+    // cursor position in characters
+    cursor: usize,
+}
+
+impl Deref for InputEditor {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl InputEditor {
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index(self.cursor);
+        self.text.insert(i, c);
+        self.cursor += 1;
+    }
+
+    // remove the character before the cursor
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let i = self.byte_index(self.cursor);
+            self.text.remove(i);
+        }
+    }
+
+    // remove the character under the cursor
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let i = self.byte_index(self.cursor);
+            self.text.remove(i);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    // Ctrl-U, remove everything before the cursor
+    pub fn clear_to_start(&mut self) {
+        let i = self.byte_index(self.cursor);
+        self.text.replace_range(..i, "");
+        self.cursor = 0;
+    }
+
+    // Ctrl-W, remove the word before the cursor and any spaces after it
+    pub fn delete_word(&mut self) {
+        let before: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let spaces = before.iter().rev().take_while(|c| **c == ' ').count();
+        let word = before
+            .iter()
+            .rev()
+            .skip(spaces)
+            .take_while(|c| **c != ' ')
+            .count();
+        for _ in 0..spaces + word {
+            self.backspace();
+        }
+    }
+
+    // apply an editing key, typed characters are only kept if the answer can contain them
+    pub fn handle_key(&mut self, key_event: KeyEvent, kind: InputKind) {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Char('u') if control => self.clear_to_start(),
+            KeyCode::Char('w') if control => self.delete_word(),
+            KeyCode::Char(c) if !control && kind.allows(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            _ => {}
+        }
+    }
+
+    // the text with the character under the cursor shown reversed as a caret
+    pub fn spans(&self, style: Style) -> Vec<Span<'static>> {
+        let before: String = self.text.chars().take(self.cursor).collect();
+        let under: String = self.text.chars().nth(self.cursor).unwrap_or(' ').to_string();
+        let after: String = self.text.chars().skip(self.cursor + 1).collect();
+        vec![
+            Span::styled(before, style),
+            Span::styled(under, style).reversed(),
+            Span::styled(after, style),
+        ]
+    }
+}
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    style::Stylize,
+    text::Span,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{DivisionFormat, GameConfiguration},
+    game::GameState,
+    input::InputKind,
+    util::Sign,
+};
+
+pub const KEYMAP_PATH: &str = "keymap.json";
+
+// screens with their own set of key bindings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Screen {
+    Splash,
+    Lobby,
+    Game,
+    // flash sum, digit span and market making while in play
+    Drill,
+    // the end of game screens
+    Results,
+    // the versus and session reports
+    Report,
+    Resting,
+    History,
+    Settings,
+}
+
+impl Screen {
+    // the bindings used in a game state
+    pub fn of(state: &GameState) -> Screen {
+        match state {
+            GameState::Setup => Screen::Splash,
+            GameState::Lobby => Screen::Lobby,
+            GameState::Inprogress => Screen::Game,
+            GameState::FlashSum | GameState::DigitSpan | GameState::Market => Screen::Drill,
+            GameState::EndingSplash
+            | GameState::FlashResults
+            | GameState::SpanResults
+            | GameState::MarketResults => Screen::Results,
+            GameState::VersusSplash | GameState::SessionSplash => Screen::Report,
+            GameState::Resting => Screen::Resting,
+            GameState::HistorySplash => Screen::History,
+            GameState::SettingsSpash => Screen::Settings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    Quit,
+    Start,
+    Versus,
+    MockTest,
+    Intervals,
+    Settings,
+    History,
+    StartMatch,
+    Restart,
+    EndGame,
+    ReturnToStart,
+    Pause,
+    Skip,
+    NextPlayer,
+    StartNow,
+    GhostRace,
+    // pick up a game left unfinished, only ever asked through a confirmation
+    Resume,
+}
+
+impl Action {
+    // actions that lose or cut short the game being played
+    pub const CONFIRMABLE: [Action; 4] = [
+        Action::Restart,
+        Action::EndGame,
+        Action::ReturnToStart,
+        Action::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Start => "Start",
+            Action::Versus => "Versus",
+            Action::MockTest => "Mock Test",
+            Action::Intervals => "Intervals",
+            Action::Settings => "Settings",
+            Action::History => "History",
+            Action::StartMatch => "Start Match",
+            Action::Restart => "Reset",
+            Action::EndGame => "End",
+            Action::ReturnToStart => "Return to Start",
+            Action::Pause => "Pause",
+            Action::Skip => "Skip",
+            Action::NextPlayer => "Next Player",
+            Action::StartNow => "Start Now",
+            Action::GhostRace => "Race",
+            Action::Resume => "Resume",
+        }
+    }
+}
+
+// the key bound to each action on each screen, in the order they are listed on screen
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    screens: HashMap<Screen, Vec<(Action, char)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+        let screens = HashMap::from([
+            (
+                Screen::Splash,
+                vec![
+                    (Start, 's'),
+                    (Versus, 'v'),
+                    (MockTest, 'm'),
+                    (Intervals, 'i'),
+                    (Settings, 'e'),
+                    (History, 'h'),
+                    (Quit, 'q'),
+                ],
+            ),
+            (
+                Screen::Lobby,
+                vec![(StartMatch, 's'), (Settings, 'e'), (Quit, 'q')],
+            ),
+            (
+                Screen::Game,
+                vec![
+                    (Restart, 'r'),
+                    (Quit, 'q'),
+                    (ReturnToStart, 'd'),
+                    (EndGame, 'e'),
+                    (Skip, 's'),
+                    (Pause, 'p'),
+                ],
+            ),
+            (
+                Screen::Drill,
+                vec![(EndGame, 'e'), (Quit, 'q'), (ReturnToStart, 'd')],
+            ),
+            (
+                Screen::Results,
+                vec![(Quit, 'q'), (ReturnToStart, 'd'), (NextPlayer, 's')],
+            ),
+            (Screen::Report, vec![(Quit, 'q'), (ReturnToStart, 'd')]),
+            (
+                Screen::Resting,
+                vec![(StartNow, 's'), (Quit, 'q'), (ReturnToStart, 'd')],
+            ),
+            (
+                Screen::History,
+                vec![(GhostRace, 'g'), (Quit, 'q'), (ReturnToStart, 'd')],
+            ),
+            (Screen::Settings, vec![(Quit, 'q'), (ReturnToStart, 'd')]),
+        ]);
+        Keymap { screens }
+    }
+}
+
+impl Keymap {
+    // Load the defaults with any bindings overridden from a JSON file like
+    // {"Game": {"Restart": "R", "Skip": "n"}}, a missing file leaves the defaults
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Keymap> {
+        let mut keymap = Keymap::default();
+        if !path.as_ref().exists() {
+            return Ok(keymap);
+        }
+        let mut contents = String::new();
+        File::open(path)?.read_to_string(&mut contents)?;
+        let overrides: HashMap<Screen, HashMap<Action, char>> = serde_json::from_str(&contents)?;
+        for (screen, actions) in overrides {
+            let bindings = keymap.screens.entry(screen).or_default();
+            for (action, key) in actions {
+                match bindings.iter_mut().find(|(a, _)| *a == action) {
+                    Some(binding) => binding.1 = key,
+                    None => bindings.push((action, key)),
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    // the action a key press triggers on a screen, keys held with control are never bound
+    pub fn action(&self, screen: Screen, key_event: KeyEvent) -> Option<Action> {
+        let KeyCode::Char(c) = key_event.code else {
+            return None;
+        };
+        if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+            return None;
+        }
+        self.screens
+            .get(&screen)?
+            .iter()
+            .find(|(_, key)| *key == c)
+            .map(|(action, _)| *action)
+    }
+
+    pub fn key(&self, screen: Screen, action: Action) -> Option<char> {
+        self.screens
+            .get(&screen)?
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, key)| *key)
+    }
+
+    // instruction spans like " Quit <Q>" for the given actions, in the order given
+    pub fn spans(&self, screen: Screen, actions: &[Action]) -> Vec<Span<'static>> {
+        let mut spans = vec![];
+        for action in actions {
+            if let Some(key) = self.key(screen, *action) {
+                spans.push(format!(" {} ", action.label()).into());
+                spans.push(format!("<{}>", key.to_ascii_uppercase()).blue().bold());
+            }
+        }
+        spans.push(" ".into());
+        spans
+    }
+
+    // Keys bound twice on one screen, and keys that can begin an answer. Bound keys that
+    // only appear later in an answer, like the e of 3e5, are typed once an answer is started
+    pub fn validate(&self, config: &GameConfiguration) -> Vec<String> {
+        let mut problems = vec![];
+        let mut screens: Vec<_> = self.screens.iter().collect();
+        screens.sort_by_key(|(screen, _)| format!("{:?}", screen));
+        for (screen, bindings) in screens {
+            for (i, (action, key)) in bindings.iter().enumerate() {
+                if let Some((other, _)) = bindings[..i].iter().find(|(_, k)| k == key) {
+                    problems.push(format!(
+                        "{:?}: '{}' is bound to both {} and {}",
+                        screen,
+                        key,
+                        other.label(),
+                        action.label()
+                    ));
+                }
+            }
+        }
+
+        let answers = [
+            (Screen::Game, answer_kinds(config)),
+            (Screen::Drill, vec![InputKind::Integer, InputKind::Quote]),
+        ];
+        for (screen, kinds) in answers {
+            for (action, key) in self.screens.get(&screen).into_iter().flatten() {
+                if kinds.iter().any(|kind| starts_answer(*kind, *key)) {
+                    problems.push(format!(
+                        "{:?}: '{}' for {} can also start an answer",
+                        screen,
+                        key,
+                        action.label()
+                    ));
+                }
+            }
+        }
+        for (action, key) in self.screens.get(&Screen::Game).into_iter().flatten() {
+            if config.multiple_choice && "12345".contains(*key) {
+                problems.push(format!(
+                    "Game: '{}' for {} is also a multiple choice option",
+                    key,
+                    action.label()
+                ));
+            }
+        }
+        problems
+    }
+}
+
+// the kinds of answer that can be asked for with this config
+fn answer_kinds(config: &GameConfiguration) -> Vec<InputKind> {
+    let mut kinds = vec![InputKind::Integer];
+    if config.estimation {
+        kinds.push(InputKind::Scientific);
+    }
+    if config.signs.contains(&Sign::Prime) {
+        kinds.push(InputKind::YesNo);
+    }
+    if config.signs.contains(&Sign::Factorise) {
+        kinds.push(InputKind::Factorisation);
+    }
+    if config.signs.contains(&Sign::Divide) {
+        match config.division_format {
+            DivisionFormat::Exact => {}
+            DivisionFormat::Remainder => kinds.push(InputKind::Remainder),
+            DivisionFormat::Decimal(_) => kinds.push(InputKind::Decimal),
+            DivisionFormat::MixedFraction => kinds.push(InputKind::Fraction),
+        }
+    }
+    kinds
+}
+
+// whether a character can be the first one typed in an answer
+fn starts_answer(kind: InputKind, c: char) -> bool {
+    kind.allows(c) && (c.is_ascii_digit() || "-+.yYnN".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_binding(screen: Screen, action: Action, key: char) -> Keymap {
+        let mut keymap = Keymap::default();
+        let bindings = keymap.screens.entry(screen).or_default();
+        match bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some(binding) => binding.1 = key,
+            None => bindings.push((action, key)),
+        }
+        keymap
+    }
+
+    #[test]
+    fn default_keymap_is_valid() {
+        let mut config = GameConfiguration::default();
+        assert!(Keymap::default().validate(&config).is_empty());
+        config.signs = Sign::ALL.to_vec();
+        config.estimation = true;
+        config.multiple_choice = true;
+        config.division_format = DivisionFormat::Decimal(2);
+        assert!(Keymap::default().validate(&config).is_empty());
+    }
+
+    #[test]
+    fn keys_bound_twice_on_a_screen_are_reported() {
+        let keymap = with_binding(Screen::Game, Action::Skip, 'r');
+        let problems = keymap.validate(&GameConfiguration::default());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Game: 'r'"));
+    }
+
+    #[test]
+    fn keys_that_start_an_answer_are_reported() {
+        let keymap = with_binding(Screen::Game, Action::Skip, '-');
+        assert_eq!(keymap.validate(&GameConfiguration::default()).len(), 1);
+
+        // y and n only start an answer when primes are asked
+        let keymap = with_binding(Screen::Game, Action::Skip, 'y');
+        let mut config = GameConfiguration::default();
+        assert!(keymap.validate(&config).is_empty());
+        config.signs.push(Sign::Prime);
+        assert_eq!(keymap.validate(&config).len(), 1);
+    }
+
+    #[test]
+    fn multiple_choice_options_are_reserved() {
+        let keymap = with_binding(Screen::Game, Action::Skip, '3');
+        let mut config = GameConfiguration::default();
+        let option = |problems: Vec<String>| {
+            problems.iter().any(|p| p.contains("multiple choice option"))
+        };
+        assert!(!option(keymap.validate(&config)));
+        config.multiple_choice = true;
+        assert!(option(keymap.validate(&config)));
+        // options are only picked on the game screen
+        let keymap = with_binding(Screen::Results, Action::NextPlayer, '3');
+        assert!(keymap.validate(&config).is_empty());
+    }
+}
@@ -1,14 +1,30 @@
+mod checkpoint;
 mod config;
+mod confirm;
 pub mod event;
 pub mod event_handlers;
 pub mod game;
+mod flash;
+mod ghost;
 mod history;
+mod input;
+mod keymap;
+mod market;
+mod mastery;
+mod mouse;
+mod net;
+mod session;
+mod span;
 pub mod renderers;
 pub mod tui;
 pub mod util;
+mod versus;
 
+use config::GameConfiguration;
 use event::{Event, EventHandler};
-use game::{AppResult, MathAnswer, MathGame};
+use game::{AppResult, GameState, MathAnswer, MathGame};
+use keymap::{Keymap, KEYMAP_PATH};
+use net::Multiplayer;
 use ratatui::{prelude::CrosstermBackend, Terminal};
 use std::io;
 use tui::Tui;
@@ -23,8 +39,34 @@ use tui::Tui;
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
+    // Load the key bindings before taking over the terminal, so errors are readable.
+    let keymap = Keymap::new(KEYMAP_PATH);
+    let problems = match &keymap {
+        Ok(keymap) => keymap.validate(&GameConfiguration::default()),
+        Err(e) => vec![e.to_string()],
+    };
+    if !problems.is_empty() {
+        eprintln!("{}:", KEYMAP_PATH);
+        for problem in problems {
+            eprintln!("  {}", problem);
+        }
+        std::process::exit(1);
+    }
+
     // Create an application.
-    let mut app = MathGame::default();
+    let mut app = MathGame {
+        keymap: keymap?,
+        ..Default::default()
+    };
+
+    // Host or join a networked match before taking over the terminal, so errors are readable.
+    let args: Vec<String> = std::env::args().collect();
+    app.multiplayer = Multiplayer::from_args(&args).await?;
+    if app.multiplayer.is_some() {
+        app.gamestate = GameState::Lobby;
+    } else {
+        app.offer_resume();
+    }
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(io::stdout());
@@ -41,7 +83,7 @@ async fn main() -> AppResult<()> {
         match tui.events.next().await? {
             Event::Tick => event_handlers::handle_tick_event(&mut app),
             Event::Key(key_event) => event_handlers::handle_events(key_event, &mut app)?,
-            // Event::Mouse(_) => {}
+            Event::Mouse(mouse_event) => event_handlers::handle_mouse_event(mouse_event, &mut app),
             Event::Resize(_, _) => {}
         }
     }
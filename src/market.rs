@@ -0,0 +1,119 @@
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfiguration;
+
+// which side the app took against the player's quote
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Trade {
+    // the app bought at the player's ask, leaving the player short
+    Bought,
+    // the app sold at the player's bid, leaving the player long
+    Sold,
+}
+
+impl Trade {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trade::Bought => "You sold",
+            Trade::Sold => "You bought",
+        }
+    }
+}
+
+// one quote made by the player and the trade done against it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketRound {
+    // dice that were visible when the quote was made
+    pub revealed: Vec<i32>,
+    pub bid: i32,
+    pub ask: i32,
+    pub trade: Trade,
+    // profit of the trade once settled at the true sum
+    pub pnl: i32,
+}
+
+// make a market on the sum of some hidden dice, one die is revealed after each quote
+#[derive(Debug, Clone)]
+pub struct Market {
+    pub dice: Vec<i32>,
+    pub rounds: Vec<MarketRound>,
+    pub max_width: i32,
+    // why the last quote was rejected
+    pub error: Option<String>,
+}
+
+impl Market {
+    pub fn new(config: &GameConfiguration, rng: &mut StdRng) -> Market {
+        Market {
+            dice: (0..config.market_dice.max(1))
+                .map(|_| rng.gen_range(1..=6))
+                .collect(),
+            rounds: vec![],
+            max_width: config.market_max_width.max(1),
+            error: None,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.dice.iter().sum()
+    }
+
+    // dice revealed so far, one more after every quote
+    pub fn revealed(&self) -> &[i32] {
+        &self.dice[..self.rounds.len().min(self.dice.len())]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.rounds.len() >= self.dice.len()
+    }
+
+    // take a quote written as "bid ask", the app lifts the ask when the sum is above it,
+    // hits the bid when below, and otherwise trades either side at random
+    pub fn quote(&mut self, input: &str, rng: &mut StdRng) -> bool {
+        let prices: Vec<i32> = input
+            .split_whitespace()
+            .filter_map(|p| p.parse().ok())
+            .collect();
+        let [bid, ask] = prices[..] else {
+            self.error = Some("Quote as <bid> <ask>".to_string());
+            return false;
+        };
+        if bid >= ask {
+            self.error = Some("Bid must be below ask".to_string());
+            return false;
+        }
+        if ask - bid > self.max_width {
+            self.error = Some(format!("Market must be at most {} wide", self.max_width));
+            return false;
+        }
+
+        let value = self.value();
+        let trade = if value > ask {
+            Trade::Bought
+        } else if value < bid {
+            Trade::Sold
+        } else if rng.gen_bool(0.5) {
+            Trade::Bought
+        } else {
+            Trade::Sold
+        };
+        let pnl = match trade {
+            Trade::Bought => ask - value,
+            Trade::Sold => value - bid,
+        };
+        self.rounds.push(MarketRound {
+            revealed: self.revealed().to_vec(),
+            bid,
+            ask,
+            trade,
+            pnl,
+        });
+        self.error = None;
+        true
+    }
+
+    pub fn pnl(&self) -> i32 {
+        self.rounds.iter().map(|r| r.pnl).sum()
+    }
+}
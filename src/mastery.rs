@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, seq::SliceRandom};
+
+use crate::{
+    game::{AnswerOutcome, MathAnswer},
+    history::GameHistory,
+    util::Sign,
+};
+
+// answers a cell needs before it can count as mastered, and how many recent ones are averaged
+const MASTERY_ANSWERS: usize = 3;
+
+// a times-table fact's recent results, newest last
+#[derive(Debug, Clone, Default)]
+pub struct CellStats {
+    pub recent: Vec<(AnswerOutcome, i64)>,
+}
+
+impl CellStats {
+    // mean time of the recent correct answers
+    pub fn average_ms(&self) -> Option<f32> {
+        let correct: Vec<i64> = self
+            .recent
+            .iter()
+            .filter(|(outcome, _)| *outcome == AnswerOutcome::Correct)
+            .map(|(_, ms)| *ms)
+            .collect();
+        match correct.len() {
+            0 => None,
+            n => Some(correct.iter().sum::<i64>() as f32 / n as f32),
+        }
+    }
+
+    // every recent answer correct and fast enough on average
+    pub fn is_mastered(&self, mastery_ms: i64) -> bool {
+        self.recent.len() >= MASTERY_ANSWERS
+            && self
+                .recent
+                .iter()
+                .all(|(outcome, _)| *outcome == AnswerOutcome::Correct)
+            && self.average_ms().is_some_and(|ms| ms <= mastery_ms as f32)
+    }
+}
+
+// per fact mastery of a times table, a x b and b x a share a cell
+#[derive(Debug, Clone)]
+pub struct MasteryGrid {
+    pub lower: i32,
+    pub upper: i32,
+    pub mastery_ms: i64,
+    cells: HashMap<(i32, i32), CellStats>,
+}
+
+impl MasteryGrid {
+    // build the grid from every multiplication answered in past games
+    pub fn from_history(history: &GameHistory, lower: i32, upper: i32, mastery_ms: i64) -> Self {
+        let mut grid = MasteryGrid {
+            lower,
+            upper: upper.max(lower),
+            mastery_ms,
+            cells: HashMap::new(),
+        };
+        for record in history.history.iter() {
+            for answer in record.answers.iter() {
+                grid.record(answer);
+            }
+        }
+        grid
+    }
+
+    fn key(a: i32, b: i32) -> (i32, i32) {
+        (a.min(b), a.max(b))
+    }
+
+    pub fn contains(&self, a: i32, b: i32) -> bool {
+        (self.lower..=self.upper).contains(&a) && (self.lower..=self.upper).contains(&b)
+    }
+
+    // add an answer to its cell, anything other than a table fact is ignored
+    pub fn record(&mut self, answer: &MathAnswer) {
+        let q = &answer.q;
+        if q.sign != Sign::Multiply || q.estimate.is_some() || !self.contains(q.lhs, q.rhs) {
+            return;
+        }
+        if answer.outcome == AnswerOutcome::Unanswered {
+            return;
+        }
+        let cell = self.cells.entry(Self::key(q.lhs, q.rhs)).or_default();
+        cell.recent.push((answer.outcome, answer.duration_m));
+        if cell.recent.len() > MASTERY_ANSWERS {
+            cell.recent.remove(0);
+        }
+    }
+
+    pub fn cell(&self, a: i32, b: i32) -> Option<&CellStats> {
+        self.cells.get(&Self::key(a, b))
+    }
+
+    pub fn is_mastered(&self, a: i32, b: i32) -> bool {
+        self.cell(a, b).is_some_and(|c| c.is_mastered(self.mastery_ms))
+    }
+
+    pub fn mastered_count(&self) -> usize {
+        self.facts()
+            .iter()
+            .filter(|(a, b)| self.is_mastered(*a, *b))
+            .count()
+    }
+
+    // every distinct fact in the table
+    pub fn facts(&self) -> Vec<(i32, i32)> {
+        (self.lower..=self.upper)
+            .flat_map(|a| (a..=self.upper).map(move |b| (a, b)))
+            .collect()
+    }
+
+    // pick the next fact, unseen facts first, then unmastered ones weighted by how slow they are
+    pub fn next_fact(&self, rng: &mut StdRng) -> (i32, i32) {
+        let facts = self.facts();
+        let weight = |(a, b): &(i32, i32)| match self.cell(*a, *b) {
+            None => 8.0,
+            Some(cell) if cell.is_mastered(self.mastery_ms) => 1.0,
+            Some(cell) => {
+                let slowness = cell.average_ms().unwrap_or(self.mastery_ms as f32 * 2.0)
+                    / self.mastery_ms as f32;
+                3.0 + slowness.min(3.0)
+            }
+        };
+        facts
+            .choose_weighted(rng, weight)
+            .copied()
+            .unwrap_or((self.lower, self.lower))
+    }
+}
@@ -0,0 +1,53 @@
+use ratatui::{
+    layout::{Direction, Margin, Position, Rect},
+    widgets::TableState,
+};
+
+use crate::keymap::Action;
+
+// where the clickable widgets were last drawn, reset at the start of every draw
+#[derive(Debug, Clone, Default)]
+pub struct ClickAreas {
+    // one line per splash menu option
+    pub splash_options: Vec<(Rect, Action)>,
+    pub history_table: Option<Rect>,
+    pub question_table: Option<Rect>,
+    // the question time barchart and the direction its bars are drawn in
+    pub barchart: Option<(Rect, Direction)>,
+}
+
+impl ClickAreas {
+    pub fn splash_option(&self, position: Position) -> Option<Action> {
+        self.splash_options
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .map(|(_, action)| *action)
+    }
+
+    // the question whose bar is under the position, bars are one cell wide with no gap
+    pub fn bar(&self, position: Position) -> Option<usize> {
+        let (area, direction) = self.barchart?;
+        let inner = area.inner(Margin::new(1, 1));
+        if !inner.contains(position) {
+            return None;
+        }
+        Some(match direction {
+            Direction::Vertical => position.x - inner.x,
+            Direction::Horizontal => position.y - inner.y,
+        } as usize)
+    }
+}
+
+// the row of a bordered table under the position, accounting for its header and scrolling
+pub fn table_row(
+    area: Option<Rect>,
+    header_height: u16,
+    state: &TableState,
+    position: Position,
+) -> Option<usize> {
+    let inner = area?.inner(Margin::new(1, 1));
+    if !inner.contains(position) || position.y < inner.y + header_height {
+        return None;
+    }
+    Some(state.offset() + (position.y - inner.y - header_height) as usize)
+}
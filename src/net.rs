@@ -0,0 +1,342 @@
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    sync::mpsc,
+};
+
+use crate::{
+    config::{GameConfiguration, GameMode},
+    game::AppResult,
+};
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// only this machine can join unless the host gives an address to listen on
+pub const DEFAULT_BIND: &str = "127.0.0.1";
+
+// seconds between the host starting a match and play beginning
+pub const COUNTDOWN_S: i64 = 3;
+
+/// Messages exchanged between instances, sent as one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// A client introducing itself to the host, `id` tells it apart from clients with the same name.
+    Hello { name: String, id: u64 },
+    /// The name the host accepted for the client that said hello with `id`.
+    Welcome { id: u64, name: String },
+    /// The host's current list of players.
+    Lobby { players: Vec<String> },
+    /// The agreed seed and config, play begins `countdown_ms` after it arrives so
+    /// players do not need their clocks in sync.
+    Start {
+        match_id: String,
+        seed: u64,
+        config: Box<GameConfiguration>,
+        countdown_ms: i64,
+    },
+    /// A player's live score and how many questions they have answered correctly.
+    Score {
+        player: String,
+        score: i32,
+        correct: i32,
+    },
+    /// A player's final result.
+    Finished {
+        player: String,
+        score: i32,
+        correct: i32,
+        duration_m: i64,
+    },
+    /// A player whose connection to the host closed.
+    Left { player: String },
+    /// The connection to the host closed, only ever raised locally.
+    Closed,
+    /// A client's connection to the host closed, only ever raised locally on the host.
+    Dropped { id: u64 },
+}
+
+/// Channels to and from the network tasks.
+#[derive(Debug)]
+pub struct Connection {
+    outgoing: mpsc::UnboundedSender<NetMessage>,
+    incoming: mpsc::UnboundedReceiver<NetMessage>,
+}
+
+impl Connection {
+    /// Listen for players, outgoing messages are broadcast to every client.
+    pub async fn host(address: &str) -> AppResult<Connection> {
+        let listener = TcpListener::bind(address).await?;
+        let (in_tx, in_rx) = mpsc::unbounded_channel();
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<NetMessage>();
+        let clients: Arc<Mutex<Vec<mpsc::UnboundedSender<String>>>> = Default::default();
+
+        let accept_clients = clients.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (reader, writer) = stream.into_split();
+                let (tx, rx) = mpsc::unbounded_channel();
+                accept_clients.lock().unwrap().push(tx);
+                tokio::spawn(write_lines(writer, rx));
+                let in_tx = in_tx.clone();
+                tokio::spawn(async move {
+                    if let Some(id) = read_messages(reader, in_tx.clone()).await {
+                        let _ = in_tx.send(NetMessage::Dropped { id });
+                    }
+                });
+            }
+        });
+
+        tokio::spawn(async move {
+            while let Some(message) = out_rx.recv().await {
+                if let Ok(line) = serde_json::to_string(&message) {
+                    // drop clients whose writer has gone away
+                    clients
+                        .lock()
+                        .unwrap()
+                        .retain(|client| client.send(line.clone()).is_ok());
+                }
+            }
+        });
+
+        Ok(Connection {
+            outgoing: out_tx,
+            incoming: in_rx,
+        })
+    }
+
+    /// Connect to a host, outgoing messages go only to the host.
+    pub async fn join(address: &str) -> AppResult<Connection> {
+        let stream = TcpStream::connect(address).await?;
+        let (reader, writer) = stream.into_split();
+        let (in_tx, in_rx) = mpsc::unbounded_channel();
+        let (out_tx, mut out_rx) = mpsc::unbounded_channel::<NetMessage>();
+        let (line_tx, line_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(write_lines(writer, line_rx));
+        tokio::spawn(async move {
+            read_messages(reader, in_tx.clone()).await;
+            let _ = in_tx.send(NetMessage::Closed);
+        });
+        tokio::spawn(async move {
+            while let Some(message) = out_rx.recv().await {
+                if let Ok(line) = serde_json::to_string(&message) {
+                    if line_tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Connection {
+            outgoing: out_tx,
+            incoming: in_rx,
+        })
+    }
+
+    pub fn send(&self, message: NetMessage) {
+        let _ = self.outgoing.send(message);
+    }
+
+    /// Next received message without blocking, polled from the tick handler.
+    pub fn try_recv(&mut self) -> Option<NetMessage> {
+        self.incoming.try_recv().ok()
+    }
+}
+
+async fn write_lines(mut writer: OwnedWriteHalf, mut lines: mpsc::UnboundedReceiver<String>) {
+    while let Some(line) = lines.recv().await {
+        if writer.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+// Forward messages until the connection closes, returning the id the peer said hello with
+async fn read_messages(
+    reader: OwnedReadHalf,
+    messages: mpsc::UnboundedSender<NetMessage>,
+) -> Option<u64> {
+    let mut id = None;
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if let Ok(message) = serde_json::from_str(&line) {
+            match message {
+                NetMessage::Hello { id: hello, .. } => id = Some(hello),
+                // only raised locally, a peer sending them is ignored
+                NetMessage::Closed | NetMessage::Dropped { .. } => continue,
+                _ => {}
+            }
+            if messages.send(message).is_err() {
+                break;
+            }
+        }
+    }
+    id
+}
+
+#[derive(Debug, Clone)]
+pub struct PlayerStatus {
+    pub name: String,
+    pub score: i32,
+    // sprints are ranked on correct answers, the score also carries penalties
+    pub correct: i32,
+    pub finished: bool,
+    pub duration_m: i64,
+    // the id a client said hello with, only known to the host
+    pub id: Option<u64>,
+}
+
+impl PlayerStatus {
+    fn new(name: String) -> PlayerStatus {
+        PlayerStatus {
+            id: None,
+            name,
+            score: 0,
+            correct: 0,
+            finished: false,
+            duration_m: 0,
+        }
+    }
+}
+
+/// A networked match, the host relays every player's scores to everyone else.
+#[derive(Debug)]
+pub struct Multiplayer {
+    pub name: String,
+    pub is_host: bool,
+    // where we are listening or who we connected to
+    pub address: String,
+    pub match_id: Option<String>,
+    pub seed: Option<u64>,
+    pub start_at: Option<DateTime<Utc>>,
+    pub players: Vec<PlayerStatus>,
+    // id a client said hello with, to pick out the host's welcome for it
+    pub hello_id: Option<u64>,
+    // the score and correct count last streamed to the other players
+    pub last_sent: (i32, i32),
+    connection: Connection,
+}
+
+impl Multiplayer {
+    /// Set up hosting or joining from `--host [port|address:port]`, `--join <address>` and
+    /// `--name <name>`.
+    pub async fn from_args(args: &[String]) -> AppResult<Option<Multiplayer>> {
+        let value_after = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .and_then(|i| args.get(i + 1))
+                .filter(|v| !v.starts_with("--"))
+        };
+
+        if args.iter().any(|a| a == "--host") {
+            let address = match value_after("--host") {
+                Some(address) if address.contains(':') => address.clone(),
+                Some(port) => format!("{}:{}", DEFAULT_BIND, port.parse::<u16>()?),
+                None => format!("{}:{}", DEFAULT_BIND, DEFAULT_PORT),
+            };
+            let name = value_after("--name").cloned().unwrap_or("host".to_string());
+            let connection = Connection::host(&address).await?;
+            return Ok(Some(Multiplayer::new(name, true, address, connection)));
+        }
+
+        if let Some(address) = value_after("--join") {
+            let name = value_after("--name")
+                .cloned()
+                .unwrap_or(format!("player-{}", rand::random::<u16>()));
+            let connection = Connection::join(address).await?;
+            let id = rand::random();
+            connection.send(NetMessage::Hello {
+                name: name.clone(),
+                id,
+            });
+            let mut multiplayer = Multiplayer::new(name, false, address.clone(), connection);
+            multiplayer.hello_id = Some(id);
+            return Ok(Some(multiplayer));
+        }
+
+        Ok(None)
+    }
+
+    fn new(name: String, is_host: bool, address: String, connection: Connection) -> Multiplayer {
+        Multiplayer {
+            players: vec![PlayerStatus::new(name.clone())],
+            name,
+            is_host,
+            address,
+            match_id: None,
+            seed: None,
+            start_at: None,
+            hello_id: None,
+            last_sent: (0, 0),
+            connection,
+        }
+    }
+
+    pub fn send(&self, message: NetMessage) {
+        self.connection.send(message);
+    }
+
+    pub fn try_recv(&mut self) -> Option<NetMessage> {
+        self.connection.try_recv()
+    }
+
+    pub fn remove_player(&mut self, name: &str) {
+        self.players.retain(|p| p.name != name);
+    }
+
+    // host only, give a client the name it asked for or the first free one after it
+    pub fn add_client(&mut self, name: &str, id: u64) -> String {
+        let mut unique = name.to_string();
+        let mut n = 2;
+        while self.players.iter().any(|p| p.name == unique) {
+            unique = format!("{}-{}", name, n);
+            n += 1;
+        }
+        self.player_mut(&unique).id = Some(id);
+        unique
+    }
+
+    pub fn player_mut(&mut self, name: &str) -> &mut PlayerStatus {
+        if let Some(i) = self.players.iter().position(|p| p.name == name) {
+            return &mut self.players[i];
+        }
+        // players joining part way through a match sit it out rather than hold up the next one
+        let mut status = PlayerStatus::new(name.to_string());
+        status.finished = self.match_id.is_some();
+        self.players.push(status);
+        self.players.last_mut().unwrap()
+    }
+
+    // whether a match has been played and someone is still playing it
+    pub fn match_in_progress(&self) -> bool {
+        self.match_id.is_some() && self.players.iter().any(|p| !p.finished)
+    }
+
+    // every player's scores are zeroed when a new match starts
+    pub fn reset_scores(&mut self) {
+        self.last_sent = (0, 0);
+        for player in self.players.iter_mut() {
+            *player = PlayerStatus::new(player.name.clone());
+        }
+    }
+
+    /// Players best first, using the same ordering as the leaderboards.
+    pub fn ranking(&self, mode: GameMode) -> Vec<&PlayerStatus> {
+        let mut ranked: Vec<&PlayerStatus> = self.players.iter().collect();
+        ranked.sort_by(|a, b| match mode {
+            GameMode::Sprint => b
+                .correct
+                .cmp(&a.correct)
+                .then(a.duration_m.cmp(&b.duration_m)),
+            _ => b.score.cmp(&a.score),
+        });
+        ranked
+    }
+}
@@ -3,17 +3,21 @@ pub mod screen;
 pub mod table;
 
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::Stylize,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
     symbols::border,
     text::{Line, Span, Text},
-    widgets::{Block, Paragraph, Wrap},
+    widgets::{block::Title, Block, BorderType, Cell, Clear, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
-use crate::game::MathGame;
+use crate::{
+    config::Setting,
+    game::{AnswerOutcome, MathGame},
+    keymap::{Action, Screen},
+};
 
-pub fn render_game_splash(frame: &mut Frame, _game: &mut MathGame) {
+pub fn render_game_splash(frame: &mut Frame, game: &mut MathGame) {
     let outer_layout = Layout::new(
         Direction::Vertical,
         vec![
@@ -32,10 +36,43 @@ pub fn render_game_splash(frame: &mut Frame, _game: &mut MathGame) {
 
     // build text objects
     let splash_text = Text::from(title_vec).alignment(Alignment::Left);
-    let options_text = (Span::from("S").underlined().bold() + Span::from("tart"))
-        // + (Span::from("S") + Span::from("e").underlined().bold() + Span::from("ttings"))
-        + (Span::from("H").underlined().bold() + Span::from("istory"))
-        + (Span::from("Q").underlined().bold() + Span::from("uit"));
+    let options: Vec<(Action, Line)> = [
+        Action::Start,
+        Action::Versus,
+        Action::MockTest,
+        Action::Intervals,
+        Action::Settings,
+        Action::History,
+        Action::Quit,
+    ]
+    .iter()
+    .filter_map(|action| {
+        let key = game.keymap.key(Screen::Splash, *action)?;
+        let label = match action {
+            Action::Start => format!("Start ({})", game.gameconfig.mode.name()),
+            _ => action.label().to_string(),
+        };
+        Some((*action, menu_option(label, key)))
+    })
+    .collect();
+
+    // each option is a centred line that can be clicked
+    let options_area = outer_layout[2];
+    for (i, (action, line)) in options
+        .iter()
+        .enumerate()
+        .take(options_area.height as usize)
+    {
+        let width = (line.width() as u16).min(options_area.width);
+        let area = Rect::new(
+            options_area.x + (options_area.width - width) / 2,
+            options_area.y + i as u16,
+            width,
+            1,
+        );
+        game.click_areas.splash_options.push((area, *action));
+    }
+    let options_text: Text = options.into_iter().map(|(_, line)| line).collect();
 
     // build splash para
     let splash_para = Paragraph::new(splash_text)
@@ -66,18 +103,726 @@ pub fn render_end_splash(frame: &mut Frame, game: &mut MathGame) {
     )
     .split(layout[0]);
 
-    screen::display_result_summary(frame, inner_layout[0], &game);
+    screen::display_result_summary(frame, inner_layout[0], game);
     table::render_table_from_questions(frame, inner_layout[1], game);
     charts::render_question_time_barchart(
         frame,
         layout[1],
-        &game,
+        game,
         Direction::Vertical,
         "Results".to_string(),
     );
 }
 // fn draw_end_splash()
 
+// quit and return keys for the end of game screens, and next player during a hot-seat game
+pub fn results_instructions(game: &MathGame) -> Vec<Span<'static>> {
+    let mut actions = vec![Action::Quit, Action::ReturnToStart];
+    if game.hotseat.as_ref().is_some_and(|h| h.next_player_waiting()) {
+        actions.push(Action::NextPlayer);
+    }
+    game.keymap.spans(Screen::Results, &actions)
+}
+
+// a menu option with its key underlined, or shown after it if the label does not contain it
+fn menu_option(label: String, key: char) -> Line<'static> {
+    match label.char_indices().find(|(_, c)| c.eq_ignore_ascii_case(&key)) {
+        Some((i, c)) => Line::from(vec![
+            Span::from(label[..i].to_string()),
+            Span::from(c.to_string()).underlined().bold(),
+            Span::from(label[i + c.len_utf8()..].to_string()),
+        ]),
+        None => Line::from(vec![
+            Span::from(format!("{} (", label)),
+            Span::from(key.to_string()).underlined().bold(),
+            Span::from(")"),
+        ]),
+    }
+}
+
+pub fn render_flash_sum(frame: &mut Frame, game: &mut MathGame) {
+    let Some(flash_sum) = &game.flash_sum else {
+        return;
+    };
+    let instructions = Title::from(Line::from(game.keymap.spans(
+        Screen::Drill,
+        &[Action::EndGame, Action::Quit, Action::ReturnToStart],
+    )));
+
+    let round = flash_sum.rounds.len() + 1;
+    let mut lines = vec![Line::from(""); (frame.area().height / 2).saturating_sub(3) as usize];
+    if flash_sum.is_answering() {
+        lines.push(Line::from("Sum?").bold());
+        lines.push(Line::from(""));
+        lines.push(Line::from(game.input.spans(Style::new().bold().yellow())));
+    } else {
+        lines.push(Line::from(format!(
+            "{} of {}",
+            (flash_sum.flash_index() + 1).min(flash_sum.numbers.len()),
+            flash_sum.numbers.len()
+        )));
+        lines.push(Line::from(""));
+        lines.push(match flash_sum.visible_number() {
+            Some(number) => Line::from(number.to_string()).bold().cyan(),
+            None => Line::from(""),
+        });
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(format!(
+                    " Flash Sum - Round {} of {} - Score {} ",
+                    round, flash_sum.total_rounds, game.score
+                ))
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_set(border::THICK),
+        ),
+        frame.area(),
+    );
+}
+
+pub fn render_flash_results(frame: &mut Frame, game: &mut MathGame) {
+    let Some(flash_sum) = &game.flash_sum else {
+        return;
+    };
+    let layout = Layout::new(
+        Direction::Horizontal,
+        vec![Constraint::Percentage(30), Constraint::Percentage(70)],
+    )
+    .split(frame.area());
+
+    let instructions = Title::from(Line::from(results_instructions(game)));
+
+    let answered: Vec<i64> = flash_sum.rounds.iter().map(|r| r.duration_m).collect();
+    let average = match answered.len() {
+        0 => "-".to_string(),
+        n => format!("{} ms", answered.iter().sum::<i64>() / n as i64),
+    };
+    let lines = vec![
+        Line::from(format!(
+            "Score: {} of {}",
+            flash_sum.score(),
+            flash_sum.rounds.len()
+        ))
+        .bold(),
+        Line::from(format!(
+            "{} numbers of {} digits at {} ms",
+            game.gameconfig.flash_count, game.gameconfig.flash_digits, flash_sum.flash_ms
+        )),
+        Line::from(format!("Average answer time: {}", average)),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .title(" Flash Sum ")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_type(BorderType::Rounded),
+        ),
+        layout[0],
+    );
+    table::render_table_from_flash_rounds(frame, layout[1], game);
+}
+
+pub fn render_digit_span(frame: &mut Frame, game: &mut MathGame) {
+    let Some(digit_span) = &game.digit_span else {
+        return;
+    };
+    let instructions = Title::from(Line::from(game.keymap.spans(
+        Screen::Drill,
+        &[Action::EndGame, Action::Quit, Action::ReturnToStart],
+    )));
+
+    let mut lines = vec![Line::from(""); (frame.area().height / 2).saturating_sub(3) as usize];
+    if digit_span.is_answering() {
+        let prompt = if digit_span.reversed {
+            "Type it backwards"
+        } else {
+            "Type it back"
+        };
+        lines.push(Line::from(prompt).bold());
+        lines.push(Line::from(""));
+        lines.push(Line::from(game.input.spans(Style::new().bold().yellow())));
+    } else {
+        lines.push(Line::from(format!(
+            "{:.1}s",
+            digit_span.remaining_ms() as f64 / 1000.0
+        )));
+        lines.push(Line::from(""));
+        lines.push(Line::from(digit_span.digits.clone()).bold().cyan());
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(format!(
+                    " Digit Span - Trial {} of {} - Span {} - Best {} ",
+                    digit_span.trials.len() + 1,
+                    digit_span.total_trials,
+                    digit_span.span,
+                    digit_span.max_span()
+                ))
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_set(border::THICK),
+        ),
+        frame.area(),
+    );
+}
+
+pub fn render_span_results(frame: &mut Frame, game: &mut MathGame) {
+    let Some(digit_span) = &game.digit_span else {
+        return;
+    };
+    let outer_layout = Layout::new(
+        Direction::Vertical,
+        vec![Constraint::Percentage(70), Constraint::Percentage(30)],
+    )
+    .split(frame.area());
+    let layout = Layout::new(
+        Direction::Horizontal,
+        vec![Constraint::Percentage(30), Constraint::Percentage(70)],
+    )
+    .split(outer_layout[0]);
+
+    let instructions = Title::from(Line::from(results_instructions(game)));
+
+    let correct = digit_span.trials.iter().filter(|t| t.is_correct()).count();
+    let best_ever = game
+        .game_history
+        .max_span_history()
+        .iter()
+        .map(|(_, span)| *span)
+        .max()
+        .unwrap_or(0);
+    let lines = vec![
+        Line::from(format!("Max span: {}", digit_span.max_span())).bold(),
+        Line::from(format!("Correct: {} of {}", correct, digit_span.trials.len())),
+        Line::from(if digit_span.reversed {
+            "Reversed"
+        } else {
+            "Forward"
+        }),
+        Line::from(format!("Best ever: {}", best_ever)),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .title(" Digit Span ")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_type(BorderType::Rounded),
+        ),
+        layout[0],
+    );
+    table::render_table_from_span_trials(frame, layout[1], game);
+    charts::render_max_span_graph(frame, outer_layout[1], game);
+}
+
+pub fn render_market(frame: &mut Frame, game: &mut MathGame) {
+    let Some(market) = &game.market else {
+        return;
+    };
+    let mut instruction_spans = vec![" Quote ".into(), "<bid ask Enter>".blue().bold()];
+    instruction_spans.append(&mut game.keymap.spans(
+        Screen::Drill,
+        &[Action::EndGame, Action::Quit, Action::ReturnToStart],
+    ));
+    let instructions = Title::from(Line::from(instruction_spans));
+
+    let dice = market.dice.len();
+    let shown: Vec<String> = (0..dice)
+        .map(|i| match market.revealed().get(i) {
+            Some(die) => format!("[{}]", die),
+            None => "[?]".to_string(),
+        })
+        .collect();
+    let mut lines = vec![Line::from(""); (frame.area().height / 2).saturating_sub(5) as usize];
+    lines.push(Line::from(format!(
+        "Make a market on the sum of {} dice ({} to {}), at most {} wide",
+        dice,
+        dice,
+        dice * 6,
+        market.max_width
+    )));
+    lines.push(Line::from(""));
+    lines.push(Line::from(shown.join(" ")).bold().cyan());
+    lines.push(Line::from(""));
+    if let Some(last) = market.rounds.last() {
+        let price = match last.trade {
+            crate::market::Trade::Bought => last.ask,
+            crate::market::Trade::Sold => last.bid,
+        };
+        lines.push(Line::from(format!(
+            "{} at {} on your {} @ {} market",
+            last.trade.name(),
+            price,
+            last.bid,
+            last.ask
+        )));
+    }
+    lines.push(Line::from(game.input.spans(Style::new().bold().yellow())));
+    if let Some(error) = &market.error {
+        lines.push(Line::from(error.clone()).red());
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(format!(
+                    " Market Making - Round {} of {} ",
+                    market.rounds.len() + 1,
+                    dice
+                ))
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_set(border::THICK),
+        ),
+        frame.area(),
+    );
+}
+
+pub fn render_market_results(frame: &mut Frame, game: &mut MathGame) {
+    let Some(market) = &game.market else {
+        return;
+    };
+    let layout = Layout::new(
+        Direction::Horizontal,
+        vec![Constraint::Percentage(30), Constraint::Percentage(70)],
+    )
+    .split(frame.area());
+
+    let instructions = Title::from(Line::from(results_instructions(game)));
+
+    let pnl = market.pnl();
+    let pnl_line = Line::from(format!("P&L: {:+}", pnl)).bold();
+    let dice: Vec<String> = market.dice.iter().map(|d| d.to_string()).collect();
+    let lines = vec![
+        if pnl >= 0 {
+            pnl_line.green()
+        } else {
+            pnl_line.red()
+        },
+        Line::from(format!("Sum: {}", market.value())),
+        Line::from(format!("Dice: {}", dice.join(" "))),
+        Line::from(format!("Max width: {}", market.max_width)),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::bordered()
+                .title(" Market Making ")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_type(BorderType::Rounded),
+        ),
+        layout[0],
+    );
+    table::render_table_from_market_rounds(frame, layout[1], game);
+}
+
+pub fn render_rest_splash(frame: &mut Frame, game: &mut MathGame) {
+    let Some(session) = &game.session else {
+        return;
+    };
+    let remaining = session
+        .resting_until
+        .map_or(0, |t| (t - chrono::Local::now()).num_milliseconds().max(0));
+    let last = session
+        .records
+        .last()
+        .and_then(|i| game.game_history.history.get(*i));
+
+    let instructions = Title::from(Line::from(game.keymap.spans(
+        Screen::Resting,
+        &[Action::StartNow, Action::Quit, Action::ReturnToStart],
+    )));
+
+    let mut lines = vec![
+        Line::from("Rest").yellow().bold(),
+        Line::from(format!(
+            "{} of {} done, next starts in {}s",
+            session.records.len(),
+            session.sections.len(),
+            remaining / 1000 + 1
+        )),
+    ];
+    if let Some(last) = last {
+        lines.push(Line::from(""));
+        lines.push(Line::from(format!("Last interval: {}", last.score)));
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(format!(" {} ", session.name))
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_set(border::DOUBLE),
+        ),
+        frame.area(),
+    );
+}
+
+pub fn render_session_splash(frame: &mut Frame, game: &mut MathGame) {
+    let Some(session) = &game.session else {
+        return;
+    };
+    let records = game.game_history.session_records(&session.id);
+
+    let layout = Layout::new(
+        Direction::Vertical,
+        vec![
+            Constraint::Min(0),
+            Constraint::Percentage(40),
+            Constraint::Length(8),
+        ],
+    )
+    .split(frame.area());
+
+    let instructions = Title::from(Line::from(
+        game.keymap
+            .spans(Screen::Report, &[Action::Quit, Action::ReturnToStart]),
+    ));
+
+    let header = ["Section", "Time", "Score", "Wrong", "Skipped", "Accuracy", "Average"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .height(1);
+
+    let accuracy = |correct: usize, attempted: usize| match attempted {
+        0 => "-".to_string(),
+        n => format!("{:.0}%", correct as f64 * 100.0 / n as f64),
+    };
+
+    let mut rows: Vec<Row> = vec![];
+    for record in records.iter() {
+        let correct = record.count(AnswerOutcome::Correct);
+        let wrong = record.count(AnswerOutcome::Wrong);
+        rows.push(Row::new(vec![
+            Line::from(record.mode_label()),
+            Line::from(format!("{:.0}s", record.duration_m as f64 / 1000.0)),
+            Line::from(record.score.to_string()),
+            Line::from(wrong.to_string()),
+            Line::from(record.count(AnswerOutcome::Skipped).to_string()),
+            Line::from(accuracy(correct, correct + wrong)),
+            Line::from(
+                record
+                    .average_correct_ms()
+                    .map_or("-".to_string(), |a| format!("{} ms", a)),
+            ),
+        ]));
+    }
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title(format!(" {} Report ", session.name))
+            .border_type(BorderType::Rounded),
+    )
+    .column_spacing(1);
+    frame.render_widget(table, layout[0]);
+    charts::render_session_speed_chart(frame, layout[1], &records);
+
+    // totals across every section
+    let correct: usize = records.iter().map(|r| r.count(AnswerOutcome::Correct)).sum();
+    let wrong: usize = records.iter().map(|r| r.count(AnswerOutcome::Wrong)).sum();
+    let skipped: usize = records.iter().map(|r| r.count(AnswerOutcome::Skipped)).sum();
+    let total_ms: i64 = records.iter().map(|r| r.duration_m).sum();
+    let overall = vec![
+        Line::from(format!("Total score: {}", records.iter().map(|r| r.score).sum::<i32>())).bold(),
+        Line::from(format!("Total time: {:.0}s", total_ms as f64 / 1000.0)),
+        Line::from(format!("Accuracy: {}", accuracy(correct, correct + wrong))),
+        Line::from(format!("Wrong: {}  Skipped: {}", wrong, skipped)),
+    ];
+    frame.render_widget(
+        Paragraph::new(overall).block(
+            Block::bordered()
+                .title(" Overall ")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_type(BorderType::Rounded),
+        ),
+        layout[2],
+    );
+}
+
+pub fn render_lobby_splash(frame: &mut Frame, game: &mut MathGame) {
+    let Some(multiplayer) = &game.multiplayer else {
+        return;
+    };
+
+    let actions = match multiplayer.is_host {
+        true => vec![Action::StartMatch, Action::Settings, Action::Quit],
+        false => vec![Action::Quit],
+    };
+    let instructions = Title::from(Line::from(game.keymap.spans(Screen::Lobby, &actions)));
+
+    let role = if multiplayer.is_host {
+        format!("Hosting on {}", multiplayer.address)
+    } else {
+        format!("Joined {}", multiplayer.address)
+    };
+    let mut lines = vec![
+        Line::from(role).bold(),
+        Line::from(format!(
+            "{} - {}s",
+            game.gameconfig.mode.name(),
+            game.gameconfig.timer
+        )),
+        Line::from(""),
+    ];
+    match multiplayer.start_at {
+        Some(start_at) => {
+            let remaining = (start_at - chrono::Utc::now()).num_milliseconds().max(0);
+            lines.push(
+                Line::from(format!("Starting in {}", remaining / 1000 + 1))
+                    .yellow()
+                    .bold(),
+            );
+        }
+        None if multiplayer.match_in_progress() => {
+            lines.push(Line::from("Waiting for everyone to finish..."))
+        }
+        None if multiplayer.is_host => lines.push(Line::from("Waiting for players...")),
+        None => lines.push(Line::from("Waiting for the host to start...")),
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Players").underlined());
+    for player in multiplayer.players.iter() {
+        let line = match multiplayer.match_id.is_some() && !player.finished {
+            true => Line::from(format!("{} (playing)", player.name)),
+            false => Line::from(player.name.clone()),
+        };
+        lines.push(if player.name == multiplayer.name {
+            line.cyan().bold()
+        } else {
+            line
+        });
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(" Lobby ")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_set(border::ROUNDED),
+        ),
+        frame.area(),
+    );
+}
+
+pub fn render_versus_splash(frame: &mut Frame, game: &mut MathGame) {
+    let Some(hotseat) = &game.hotseat else {
+        return;
+    };
+    let records: Vec<_> = hotseat
+        .records
+        .iter()
+        .filter_map(|i| game.game_history.history.get(*i))
+        .collect();
+    if records.len() < 2 {
+        return;
+    }
+
+    let outer_layout = Layout::new(
+        Direction::Vertical,
+        vec![Constraint::Length(3), Constraint::Min(0)],
+    )
+    .split(frame.area());
+    let layout = Layout::new(
+        Direction::Horizontal,
+        vec![Constraint::Percentage(50), Constraint::Percentage(50)],
+    )
+    .split(outer_layout[1]);
+
+    let ordering = records[0].compare(records[1]);
+    let verdict = match ordering {
+        std::cmp::Ordering::Greater => "Player 1 wins!".green().bold(),
+        std::cmp::Ordering::Less => "Player 2 wins!".green().bold(),
+        std::cmp::Ordering::Equal => "It's a draw!".yellow().bold(),
+    };
+    let instructions = Title::from(Line::from(
+        game.keymap
+            .spans(Screen::Report, &[Action::Quit, Action::ReturnToStart]),
+    ));
+    frame.render_widget(
+        Paragraph::new(Line::from(verdict))
+            .alignment(Alignment::Center)
+            .block(
+                Block::bordered()
+                    .border_set(border::DOUBLE)
+                    .title(
+                        instructions
+                            .alignment(Alignment::Center)
+                            .position(ratatui::widgets::block::Position::Bottom),
+                    ),
+            ),
+        outer_layout[0],
+    );
+
+    for (i, record) in records.iter().enumerate() {
+        let winner = match i {
+            0 => ordering == std::cmp::Ordering::Greater,
+            _ => ordering == std::cmp::Ordering::Less,
+        };
+        screen::display_player_summary(
+            frame,
+            layout[i],
+            format!("Player {}", i + 1),
+            record,
+            winner,
+        );
+    }
+}
+
+// the pending confirmation as a box in the middle of the screen
+pub fn render_confirm(frame: &mut Frame, game: &mut MathGame) {
+    let Some(confirm) = &game.confirm else {
+        return;
+    };
+    let question = confirm.question(game);
+    let area = frame.area();
+    let width = (question.len() as u16 + 6).max(30).min(area.width);
+    let height = 5.min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    let lines = vec![
+        Line::from(question).bold(),
+        Line::from(""),
+        Line::from(vec![
+            " Yes ".into(),
+            "<Y/Enter>".blue().bold(),
+            " No ".into(),
+            "<N/Esc>".blue().bold(),
+        ]),
+    ];
+    frame.render_widget(Clear, popup);
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::bordered()
+                .title(" Confirm ")
+                .border_set(border::DOUBLE)
+                .yellow(),
+        ),
+        popup,
+    );
+}
+
+pub fn render_settings_splash(frame: &mut Frame, game: &mut MathGame) {
+    let mut instruction_spans = vec![" Change ".into(), "<Left/Right>".blue().bold()];
+    instruction_spans.append(
+        &mut game
+            .keymap
+            .spans(Screen::Settings, &[Action::Quit, Action::ReturnToStart]),
+    );
+    let instructions = Title::from(Line::from(instruction_spans));
+
+    let header = ["Setting", "Value"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .height(1);
+
+    let rows: Vec<Row> = Setting::ALL
+        .iter()
+        .map(|s| {
+            Row::new(vec![
+                Line::from(s.name()),
+                Line::from(s.value(&game.gameconfig)).bold(),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(rows, [Constraint::Length(30), Constraint::Length(12)])
+        .header(header)
+        .block(
+            Block::bordered()
+                .title("Settings")
+                .title(
+                    instructions
+                        .alignment(Alignment::Center)
+                        .position(ratatui::widgets::block::Position::Bottom),
+                )
+                .border_type(BorderType::Rounded),
+        )
+        .highlight_style(Style::new().bg(Color::DarkGray))
+        .highlight_symbol(">>")
+        .column_spacing(1);
+
+    // key bindings that clash with each other or with answers under these settings
+    let conflicts = game.keymap.validate(&game.gameconfig);
+    let layout = Layout::new(
+        Direction::Vertical,
+        vec![
+            Constraint::Min(0),
+            Constraint::Length(match conflicts.len() {
+                0 => 0,
+                n => n as u16 + 2,
+            }),
+        ],
+    )
+    .split(frame.area());
+    frame.render_stateful_widget(table, layout[0], &mut game.settings_table_state);
+    if !conflicts.is_empty() {
+        let lines: Vec<Line> = conflicts.into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines).yellow().block(
+                Block::bordered()
+                    .title(" Key Conflicts ")
+                    .border_type(BorderType::Rounded),
+            ),
+            layout[1],
+        );
+    }
+}
+
 pub fn render_history_splash(frame: &mut Frame, game: &mut MathGame) {
     let outer_layout = Layout::new(
         Direction::Vertical,
@@ -97,13 +842,26 @@ pub fn render_history_splash(frame: &mut Frame, game: &mut MathGame) {
 
     // let qtable = render_table_from_questions(history_answers);
     table::render_table_from_history(frame, layout[0], game);
-    table::render_table_from_questions(frame, layout[1], game);
-    charts::render_question_time_barchart(
-        frame,
-        layout[2],
-        game,
-        Direction::Horizontal,
-        "Times".to_string(),
-    );
+    // times table games show the mastery grid in place of the question times
+    let mastery = crate::util::get_selected_record(game)
+        .is_some_and(|r| r.mode == crate::config::GameMode::Mastery);
+    if mastery {
+        let grid_layout = Layout::new(
+            Direction::Horizontal,
+            vec![Constraint::Percentage(40), Constraint::Percentage(60)],
+        )
+        .split(layout[1].union(layout[2]));
+        table::render_table_from_questions(frame, grid_layout[0], game);
+        table::render_mastery_grid(frame, grid_layout[1], game);
+    } else {
+        table::render_table_from_questions(frame, layout[1], game);
+        charts::render_question_time_barchart(
+            frame,
+            layout[2],
+            game,
+            Direction::Horizontal,
+            "Times".to_string(),
+        );
+    }
     charts::render_score_history_graph(frame, outer_layout[1], game);
 }
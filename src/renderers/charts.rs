@@ -2,16 +2,21 @@ use ratatui::{
     layout::{Direction, Rect},
     style::{Style, Stylize},
     symbols,
+    text::Line,
     widgets::{Axis, Bar, BarChart, BarGroup, Block, Chart, Dataset, GraphType},
     Frame,
 };
 
-use crate::game::MathGame;
+use crate::{
+    config::GameMode,
+    game::{AnswerOutcome, MathGame},
+    history::GameRecord,
+};
 
 pub(crate) fn render_question_time_barchart(
     frame: &mut Frame,
     area: Rect,
-    game: &MathGame,
+    game: &mut MathGame,
     orientation: Direction,
     title: String,
 ) {
@@ -26,12 +31,18 @@ pub(crate) fn render_question_time_barchart(
 
     let bars: Vec<Bar> = data
         .into_iter()
-        .map(|(u, f)| {
-            Bar::default()
+        .map(|(u, f)| match target_answers[u].outcome {
+            // timeouts are drawn apart from the speed gradient
+            AnswerOutcome::TimedOut => Bar::default()
+                .value(f as u64)
+                .style(Style::new().dark_gray())
+                .text_value(format!("{:<6}ms timeout ", f))
+                .value_style(Style::new().blue().bold()),
+            _ => Bar::default()
                 .value(f as u64)
                 .style(colors[u])
                 .text_value(format!("{:<6}ms ", f))
-                .value_style(colors[u])
+                .value_style(colors[u]),
         })
         .collect();
 
@@ -45,24 +56,75 @@ pub(crate) fn render_question_time_barchart(
         .data(BarGroup::default().bars(&bars));
 
     frame.render_widget(barchart, area);
+    game.click_areas.barchart = Some((area, orientation));
 }
 
 pub fn render_score_history_graph(frame: &mut Frame, area: Rect, game: &MathGame) {
-    let mut d1: Vec<(f64, f64)> = vec![];
+    // chart the history of whichever mode the selected record was played in
+    let selected = crate::util::get_selected_record(game);
+    let mode = selected.map(|r| r.mode).unwrap_or(game.gameconfig.mode);
+    let records = game.game_history.records_for_mode(mode);
 
-    for (i, q) in game.game_history.history.iter().enumerate() {
-        d1.push((i as f64, q.score as f64));
+    match mode {
+        // only sprints run to the end over the same number of questions share a chart
+        GameMode::Sprint => {
+            let questions = selected
+                .and_then(|r| r.sprint_questions)
+                .unwrap_or(game.gameconfig.sprint_questions);
+            let d1: Vec<(f64, f64)> = records
+                .iter()
+                .filter(|r| r.is_finished_sprint() && r.is_sprint_of(questions))
+                .enumerate()
+                .map(|(i, r)| (i as f64, r.duration_m as f64 / 1000.0))
+                .collect();
+            render_history_chart(frame, area, &d1, "Sprint Times".to_string(), "Time (s)");
+        }
+        GameMode::DigitSpan => render_max_span_graph(frame, area, game),
+        _ => {
+            let d1: Vec<(f64, f64)> = records
+                .iter()
+                .enumerate()
+                .map(|(i, r)| (i as f64, r.score as f64))
+                .collect();
+            render_history_chart(
+                frame,
+                area,
+                &d1,
+                format!("Score History ({})", mode.name()),
+                "Score",
+            );
+        }
     }
+}
+
+// longest digit span recalled in each digit span game
+pub fn render_max_span_graph(frame: &mut Frame, area: Rect, game: &MathGame) {
+    let d1: Vec<(f64, f64)> = game
+        .game_history
+        .max_span_history()
+        .iter()
+        .enumerate()
+        .map(|(i, (_, span))| (i as f64, *span as f64))
+        .collect();
+    render_history_chart(frame, area, &d1, "Max Digit Span".to_string(), "Digits");
+}
 
+fn render_history_chart(
+    frame: &mut Frame,
+    area: Rect,
+    d1: &[(f64, f64)],
+    title: String,
+    y_title: &str,
+) {
     let datasets = vec![
         // Scatter chart
         Dataset::default()
-            .name("Scores")
+            .name(y_title.to_string())
             // .marker(symbols)
             .marker(symbols::Marker::Block)
             .graph_type(GraphType::Bar)
             .style(Style::default().cyan())
-            .data(&d1),
+            .data(d1),
         // Line chart
     ];
 
@@ -75,31 +137,53 @@ pub fn render_score_history_graph(frame: &mut Frame, area: Rect, game: &MathGame
         .labels(["0.0", &binding]);
 
     // Create the Y axis and define its properties
-    let binding = d1
-        .iter()
-        .map(|f| f.1)
-        .collect::<Vec<f64>>()
-        .into_iter()
-        .fold(0. / 0., f64::max)
-        .to_string();
+    let y_max = d1.iter().map(|f| f.1).fold(f64::NAN, f64::max);
+    let binding = format!("{:.1}", y_max);
     let y_axis = Axis::default()
-        .title("Score".red())
+        .title(y_title.to_string().red())
         // .style(Style::default().white())
-        .bounds([
-            0.0,
-            d1.iter()
-                .map(|f| f.1)
-                .collect::<Vec<f64>>()
-                .into_iter()
-                .fold(0. / 0., f64::max),
-        ])
+        .bounds([0.0, y_max])
         .labels(["0.0", &binding]);
 
     // Create the chart and link all the parts together
     let chart = Chart::new(datasets)
-        .block(Block::new().title("Score History")) //consider changing this to allow variable input
+        .block(Block::new().title(title))
         .x_axis(x_axis)
         .y_axis(y_axis);
 
     frame.render_widget(chart, area);
 }
+
+// correct answers per minute in each section, showing how speed holds up across a session
+pub fn render_session_speed_chart(frame: &mut Frame, area: Rect, records: &[&GameRecord]) {
+    let rates: Vec<f64> = records
+        .iter()
+        .map(|r| match r.duration_m {
+            0 => 0.0,
+            ms => r.count(AnswerOutcome::Correct) as f64 * 60000.0 / ms as f64,
+        })
+        .collect();
+    let colors = crate::util::create_gradient(&rates.iter().map(|r| -*r as f32).collect::<Vec<f32>>());
+
+    let bars: Vec<Bar> = rates
+        .iter()
+        .enumerate()
+        .map(|(i, rate)| {
+            Bar::default()
+                .value((rate * 10.0) as u64)
+                .label(Line::from((i + 1).to_string()))
+                .style(colors[i])
+                .text_value(format!("{:.1}", rate))
+                .value_style(Style::new().black().bold().bg(colors[i]))
+        })
+        .collect();
+
+    let barchart = BarChart::default()
+        .block(Block::bordered().title("Correct per minute"))
+        .bar_width(7)
+        .bar_gap(1)
+        .label_style(Style::new().white())
+        .data(BarGroup::default().bars(&bars));
+
+    frame.render_widget(barchart, area);
+}
@@ -2,38 +2,189 @@ use std::collections::HashMap;
 
 use ratatui::{
     layout::Rect,
+    style::Stylize,
     text::Line,
     widgets::{block::Title, Block, Paragraph},
     Frame,
 };
 
-use crate::{game::MathGame, util::Sign};
+use crate::{
+    config::GameMode,
+    game::{AnswerOutcome, MathGame},
+    history::GameRecord,
+    keymap::{Action, Screen},
+    util::Sign,
+};
 
 pub(crate) fn display_result_summary(frame: &mut Frame, area: Rect, game: &MathGame) {
     let title: Title = Title::from("Results");
 
-    let grouped_sums: HashMap<Sign, i32> =
+    let correct_answers: Vec<_> = game
+        .answers
+        .iter()
+        .filter(|a| a.outcome == AnswerOutcome::Correct)
+        .collect();
+
+    // (correct, skipped) per operation
+    let grouped_sums: HashMap<Sign, (i32, i32)> =
         game.answers
-            .clone()
-            .into_iter()
+            .iter()
             .fold(HashMap::new(), |mut acc, item| {
-                *acc.entry(item.q.sign).or_insert(0) += 1;
+                let entry = acc.entry(item.q.sign).or_insert((0, 0));
+                match item.outcome {
+                    AnswerOutcome::Correct => entry.0 += 1,
+                    AnswerOutcome::Skipped => entry.1 += 1,
+                    _ => {}
+                }
                 acc
             });
 
-    let mut sorted_sums: Vec<(String, i32)> = grouped_sums
+    let mut sorted_sums: Vec<(String, (i32, i32))> = grouped_sums
         .into_iter()
         .map(|f| (f.0.to_string(), f.1))
         .collect();
     sorted_sums.sort_by(|a, b| a.0.cmp(&b.0)); // Sort by key (the String)
 
     let mut line_vec = vec![];
-    line_vec.push(Line::from(format!("Score: {}", game.answers.len() - 1)));
+    line_vec.push(Line::from(format!("Score: {}", correct_answers.len())));
+    if game.gameconfig.multiple_choice {
+        // the marked score after negative marking for wrong picks
+        let wrong = game
+            .answers
+            .iter()
+            .filter(|a| a.outcome == AnswerOutcome::Wrong)
+            .count();
+        line_vec[0] = Line::from(format!("Score: {} ({} wrong)", game.score, wrong));
+    }
+
+    for (s, (correct, skipped)) in sorted_sums {
+        if skipped > 0 {
+            line_vec.push(Line::from(format![
+                "{:<8}: {} ({} skipped)",
+                s.to_string(),
+                correct,
+                skipped
+            ]));
+        } else {
+            line_vec.push(Line::from(format!["{:<8}: {}", s.to_string(), correct]));
+        }
+    }
+
+    if game.gameconfig.estimation {
+        let points = crate::util::estimate_points(&game.answers);
+        line_vec.push(Line::from(format!("Estimate points: {}", points)));
+    }
+    if game.gameconfig.combo_scoring {
+        line_vec.push(Line::from(format!("Combo score: {}", game.combo_score)));
+        line_vec.push(Line::from(format!("Best streak: {}", game.best_streak)));
+    }
+
+    if let (Some(ghost), Some(last)) = (&game.ghost, game.game_history.history.last()) {
+        let verdict = match last.compare(&ghost.record) {
+            std::cmp::Ordering::Greater => "you won",
+            std::cmp::Ordering::Less => "ghost won",
+            std::cmp::Ordering::Equal => "draw",
+        };
+        line_vec.push(Line::from(format!(
+            "Ghost: {} ({})",
+            ghost.record.score_label(),
+            verdict
+        )));
+    }
+
+    if let Some(multiplayer) = &game.multiplayer {
+        line_vec.push(Line::from(""));
+        line_vec.push(Line::from("Match ranking"));
+        for (i, player) in multiplayer.ranking(game.gameconfig.mode).iter().enumerate() {
+            let result = match (player.finished, game.gameconfig.mode) {
+                (false, _) => "playing...".to_string(),
+                (true, GameMode::Sprint) => format!(
+                    "{} in {:.1}s",
+                    player.correct,
+                    player.duration_m as f64 / 1000.0
+                ),
+                (true, _) => player.score.to_string(),
+            };
+            line_vec.push(Line::from(format!("{}. {:<12} {}", i + 1, player.name, result)));
+        }
+    }
+
+    if game.hotseat.as_ref().is_some_and(|h| h.next_player_waiting()) {
+        line_vec.push(Line::from(""));
+        let key = game.keymap.key(Screen::Results, Action::NextPlayer).unwrap_or('s');
+        line_vec.push(
+            Line::from(format!("Player 2: press <{}> to start", key.to_ascii_uppercase()))
+                .cyan()
+                .bold(),
+        );
+    }
+
+    let mode = game.gameconfig.mode;
+    line_vec.push(Line::from(""));
+    line_vec.push(Line::from(format!("{} high scores", mode.name())));
+    if let Some(last) = game.game_history.history.last() {
+        if last.paused {
+            line_vec.insert(
+                1,
+                Line::from(format!(
+                    "Paused {:.1}s (not ranked)",
+                    last.paused_ms as f64 / 1000.0
+                )),
+            );
+        }
+        if mode == GameMode::Sprint {
+            line_vec.insert(1, Line::from(format!("Result: {}", last.score_label())));
+        }
+    }
+    let sprint_questions = game.gameconfig.sprint_questions;
+    for (i, record) in game
+        .game_history
+        .high_scores(mode, sprint_questions, 5)
+        .iter()
+        .enumerate()
+    {
+        line_vec.push(Line::from(format!(
+            "{}. {:<6} {}",
+            i + 1,
+            record.score_label(),
+            record.game_intant.format("%Y-%m-%d")
+        )));
+    }
+
+    frame.render_widget(
+        Paragraph::new(line_vec).block(Block::bordered().title(title)),
+        area,
+    );
+}
+
+// one player's results, used for the side by side versus screen
+pub(crate) fn display_player_summary(
+    frame: &mut Frame,
+    area: Rect,
+    title: String,
+    record: &GameRecord,
+    winner: bool,
+) {
+    let count = |outcome: AnswerOutcome| record.count(outcome);
+
+    let mut line_vec = vec![
+        Line::from(format!("Score: {}", record.score_label())).bold(),
+        Line::from(format!("Time: {:.1}s", record.duration_m as f64 / 1000.0)),
+        Line::from(format!("Correct: {}", count(AnswerOutcome::Correct))),
+        Line::from(format!("Wrong: {}", count(AnswerOutcome::Wrong))),
+        Line::from(format!("Skipped: {}", count(AnswerOutcome::Skipped))),
+        Line::from(format!("Timed out: {}", count(AnswerOutcome::TimedOut))),
+    ];
 
-    for (s, i) in sorted_sums {
-        line_vec.push(Line::from(format!["{:<8}: {}", s.to_string(), i]));
+    if let Some(average) = record.average_correct_ms() {
+        line_vec.push(Line::from(format!("Average: {} ms", average)));
     }
 
+    let title = if winner {
+        Title::from(Line::from(format!(" {} - Winner ", title)).green().bold())
+    } else {
+        Title::from(format!(" {} ", title))
+    };
     frame.render_widget(
         Paragraph::new(line_vec).block(Block::bordered().title(title)),
         area,
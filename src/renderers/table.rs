@@ -6,22 +6,28 @@ use ratatui::{
     Frame,
 };
 
-use crate::{game::MathGame, util::get_target_answers};
+use crate::{
+    game::MathGame,
+    keymap::{Action, Screen},
+    util::get_target_answers,
+};
+
+// header heights of the tables rows can be clicked in
+pub const QUESTION_HEADER_HEIGHT: u16 = 1;
+pub const HISTORY_HEADER_HEIGHT: u16 = 2;
 
 pub fn render_table_from_questions(frame: &mut Frame, area: Rect, game: &mut MathGame) {
     let target_answers = get_target_answers(game); //if we are in history, show a historical answer set
-    let instructions = Title::from(Line::from(vec![
-        " Quit ".into(),
-        "<Q>".blue().bold(),
-        " Return to Start ".into(),
-        "<D> ".blue().bold(),
-    ]));
+    let instructions = Title::from(Line::from(game.keymap.spans(
+        Screen::of(&game.gamestate),
+        &[Action::Quit, Action::ReturnToStart],
+    )));
 
-    let header = ["Question", "Answer", "Time", "120s Pace"]
+    let header = ["Question", "Answer", "Time", "120s Pace", "Result"]
         .into_iter()
         .map(Cell::from)
         .collect::<Row>()
-        .height(1);
+        .height(QUESTION_HEADER_HEIGHT);
     let v = target_answers
         .iter()
         .map(|f| f.duration_m as f32)
@@ -34,7 +40,7 @@ pub fn render_table_from_questions(frame: &mut Frame, area: Rect, game: &mut Mat
     for (x, i) in target_answers.iter().enumerate() {
         running_total += i.duration_m;
         let qstring = i.string_representation.to_string();
-        let astring = i.q.answer.to_string();
+        let astring = i.q.answer_label();
         let mut tstring = i.duration_m.to_string();
         let running_average: f64 = 120000.0 / (running_total / (x as i64 + 1)) as f64; // quick calc for 120 seconds
         let rstring = running_average.to_string();
@@ -45,6 +51,11 @@ pub fn render_table_from_questions(frame: &mut Frame, area: Rect, game: &mut Mat
             Line::from(astring),
             Line::from(tstring).style(Style::new().fg(colors[x])),
             Line::from(rstring),
+            // estimates show how far out they were rather than just right or wrong
+            match i.error_factor {
+                Some(factor) => Line::from(format!("x{:.2}", factor)),
+                None => Line::from(i.outcome.name()),
+            },
         ]));
     }
 
@@ -52,7 +63,8 @@ pub fn render_table_from_questions(frame: &mut Frame, area: Rect, game: &mut Mat
     let table = Table::new(
         rows,
         [
-            // + 1 is for padding.
+            // + 1 is for padding, estimates need room for e notation
+            Constraint::Length(13),
             Constraint::Length(8),
             Constraint::Length(8),
             Constraint::Length(8),
@@ -76,22 +88,29 @@ pub fn render_table_from_questions(frame: &mut Frame, area: Rect, game: &mut Mat
         "".into(),
     ]));
     frame.render_stateful_widget(table, area, &mut game.result_table_state);
+    game.click_areas.question_table = Some(area);
 }
 
 pub fn render_table_from_history(frame: &mut Frame, area: Rect, game: &mut MathGame) {
-    let header = ["#", "Date", "Score"]
+    let header = ["#", "Date", "Mode", "Score"]
         .into_iter()
         .map(Cell::from)
         .collect::<Row>()
-        .height(2);
+        .height(HISTORY_HEADER_HEIGHT);
 
     let mut rows: Vec<Row> = vec![];
     for (x, i) in game.game_history.history.iter().enumerate() {
-        rows.push(Row::new(vec![
+        let row = Row::new(vec![
             Line::from(x.to_string()),
             Line::from(i.game_intant.to_string()),
-            Line::from(i.score.to_string()),
-        ]));
+            Line::from(i.mode_label()),
+            Line::from(i.score_label()),
+        ]);
+        // abandoned games are kept for their answers but struck through
+        rows.push(match i.abandoned {
+            true => row.dark_gray().crossed_out(),
+            false => row,
+        });
     }
 
     let table = Table::new(
@@ -100,15 +119,256 @@ pub fn render_table_from_history(frame: &mut Frame, area: Rect, game: &mut MathG
             Constraint::Length(6),
             Constraint::Length(10),
             Constraint::Length(8),
+            Constraint::Length(8),
         ],
     )
     .header(header)
     .block(
         Block::bordered()
             .title("History Table")
+            .title(
+                Title::from(Line::from(
+                    game.keymap.spans(Screen::History, &[Action::GhostRace]),
+                ))
+                    .alignment(ratatui::layout::Alignment::Center)
+                    .position(ratatui::widgets::block::Position::Bottom),
+            )
             .border_type(BorderType::Rounded),
     )
     .highlight_style(Style::new().bg(Color::DarkGray))
     .column_spacing(1);
     frame.render_stateful_widget(table, area, &mut game.history_table_state);
+    game.click_areas.history_table = Some(area);
+}
+
+// each round of a flash-sum game with the numbers shown and the sum given
+pub fn render_table_from_flash_rounds(frame: &mut Frame, area: Rect, game: &mut MathGame) {
+    let Some(flash_sum) = &game.flash_sum else {
+        return;
+    };
+    let header = ["#", "Numbers", "Sum", "Answer", "Time", "Result"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .height(1);
+
+    let rows: Vec<Row> = flash_sum
+        .rounds
+        .iter()
+        .enumerate()
+        .map(|(i, round)| {
+            let numbers = round
+                .numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            let result = if round.is_correct() {
+                Line::from("Correct").green()
+            } else {
+                Line::from("Wrong").red()
+            };
+            Row::new(vec![
+                Line::from((i + 1).to_string()),
+                Line::from(numbers),
+                Line::from(round.sum().to_string()),
+                Line::from(round.answer.map_or("-".to_string(), |a| a.to_string())),
+                Line::from(format!("{} ms", round.duration_m)),
+                result,
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title("Rounds")
+            .border_type(BorderType::Rounded),
+    )
+    .highlight_style(Style::new().bg(Color::DarkGray))
+    .highlight_symbol(">>")
+    .column_spacing(1);
+    frame.render_stateful_widget(table, area, &mut game.result_table_state);
+}
+
+// each trial of a digit span game with the number shown and what was typed back
+pub fn render_table_from_span_trials(frame: &mut Frame, area: Rect, game: &mut MathGame) {
+    let Some(digit_span) = &game.digit_span else {
+        return;
+    };
+    let header = ["#", "Span", "Shown", "Expected", "Answer", "Time"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .height(1);
+
+    let rows: Vec<Row> = digit_span
+        .trials
+        .iter()
+        .enumerate()
+        .map(|(i, trial)| {
+            let answer = if trial.is_correct() {
+                Line::from(trial.answer.clone()).green()
+            } else {
+                Line::from(trial.answer.clone()).red()
+            };
+            Row::new(vec![
+                Line::from((i + 1).to_string()),
+                Line::from(trial.digits.len().to_string()),
+                Line::from(trial.digits.clone()),
+                Line::from(trial.expected()),
+                answer,
+                Line::from(format!("{} ms", trial.duration_m)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(12),
+            Constraint::Min(12),
+            Constraint::Min(12),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title("Trials")
+            .border_type(BorderType::Rounded),
+    )
+    .highlight_style(Style::new().bg(Color::DarkGray))
+    .highlight_symbol(">>")
+    .column_spacing(1);
+    frame.render_stateful_widget(table, area, &mut game.result_table_state);
+}
+
+// each quote of a market making game with the trade done and its settled profit
+pub fn render_table_from_market_rounds(frame: &mut Frame, area: Rect, game: &mut MathGame) {
+    let Some(market) = &game.market else {
+        return;
+    };
+    let header = ["#", "Known", "Bid", "Ask", "Trade", "P&L"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .height(1);
+
+    let rows: Vec<Row> = market
+        .rounds
+        .iter()
+        .enumerate()
+        .map(|(i, round)| {
+            let known = round
+                .revealed
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            let pnl = Line::from(format!("{:+}", round.pnl));
+            Row::new(vec![
+                Line::from((i + 1).to_string()),
+                Line::from(known),
+                Line::from(round.bid.to_string()),
+                Line::from(round.ask.to_string()),
+                Line::from(round.trade.name()),
+                if round.pnl >= 0 { pnl.green() } else { pnl.red() },
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Min(12),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(11),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title("Quotes")
+            .border_type(BorderType::Rounded),
+    )
+    .highlight_style(Style::new().bg(Color::DarkGray))
+    .highlight_symbol(">>")
+    .column_spacing(1);
+    frame.render_stateful_widget(table, area, &mut game.result_table_state);
+}
+
+// times table coloured by how quickly each fact is answered, mastered facts are ticked
+pub fn render_mastery_grid(frame: &mut Frame, area: Rect, game: &mut MathGame) {
+    let grid = game.history_mastery();
+    let range = grid.lower..=grid.upper;
+
+    // one gradient across every answered fact so colours compare between cells
+    let answered: Vec<(i32, i32, f32)> = grid
+        .facts()
+        .into_iter()
+        .filter_map(|(a, b)| {
+            grid.cell(a, b)
+                .and_then(|c| c.average_ms())
+                .map(|ms| (a, b, ms))
+        })
+        .collect();
+    let colors = crate::util::create_gradient(
+        &answered.iter().map(|(_, _, ms)| *ms).collect::<Vec<f32>>(),
+    );
+    let color_of = |a: i32, b: i32| {
+        answered
+            .iter()
+            .position(|(x, y, _)| (*x, *y) == (a.min(b), a.max(b)))
+            .map(|i| colors[i])
+    };
+
+    let header = std::iter::once(Cell::from("x"))
+        .chain(range.clone().map(|b| Cell::from(b.to_string())))
+        .collect::<Row>()
+        .height(1)
+        .bold();
+    let rows: Vec<Row> = range
+        .clone()
+        .map(|a| {
+            let cells = range.clone().map(|b| {
+                let mark = if grid.is_mastered(a, b) { "✓" } else { "" };
+                match color_of(a, b) {
+                    Some(color) => Cell::from(mark).style(Style::new().bg(color).black()),
+                    None => Cell::from("·").dark_gray(),
+                }
+            });
+            std::iter::once(Cell::from(a.to_string()).bold())
+                .chain(cells)
+                .collect::<Row>()
+        })
+        .collect();
+
+    let widths = vec![Constraint::Length(2); range.count() + 1];
+    let table = Table::new(rows, widths).header(header).column_spacing(1).block(
+        Block::bordered()
+            .title(format!(
+                "Mastery {}/{}",
+                grid.mastered_count(),
+                grid.facts().len()
+            ))
+            .border_type(BorderType::Rounded),
+    );
+    frame.render_widget(table, area);
 }
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Local, Utc};
+
+use crate::{config::GameConfiguration, util::Sign};
+
+// one timed game within a session
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub name: String,
+    pub config: GameConfiguration,
+}
+
+// a sequence of games played back to back and stored as a linked group in the history
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: String,
+    pub name: String,
+    pub sections: Vec<Section>,
+    // index of the section being played
+    pub current: usize,
+    // indexes into the game history of each finished section's record
+    pub records: Vec<usize>,
+    // config to restore once the session is over
+    pub base_config: GameConfiguration,
+    // seconds of rest between sections, zero plays them back to back
+    pub rest_s: i64,
+    // set while resting between sections
+    pub resting_until: Option<DateTime<Local>>,
+}
+
+// alternating work and rest intervals, written like 6x45/15
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IntervalPlan {
+    pub reps: usize,
+    pub work_s: i32,
+    pub rest_s: i64,
+}
+
+impl IntervalPlan {
+    pub fn parse(plan: &str) -> Option<IntervalPlan> {
+        let (reps, times) = plan.trim().split_once('x')?;
+        let (work, rest) = times.split_once('/')?;
+        let plan = IntervalPlan {
+            reps: reps.trim().parse().ok()?,
+            work_s: work.trim().parse().ok()?,
+            rest_s: rest.trim().parse().ok()?,
+        };
+        (plan.reps > 0 && plan.work_s > 0 && plan.rest_s >= 0).then_some(plan)
+    }
+}
+
+impl Session {
+    pub fn new(name: &str, sections: Vec<Section>, base_config: GameConfiguration) -> Session {
+        Session {
+            id: format!(
+                "{}-{:04x}",
+                Utc::now().format("%Y%m%d%H%M%S"),
+                rand::random::<u16>()
+            ),
+            name: name.to_string(),
+            sections,
+            current: 0,
+            records: vec![],
+            base_config,
+            rest_s: 0,
+            resting_until: None,
+        }
+    }
+
+    // repeated timed intervals with the current config and rests in between
+    pub fn intervals(plan: IntervalPlan, base_config: &GameConfiguration) -> Session {
+        let sections = (1..=plan.reps)
+            .map(|i| {
+                let mut config = base_config.clone();
+                config.mode = crate::config::GameMode::Timed;
+                config.timer = plan.work_s;
+                Section {
+                    name: format!("Interval {}", i),
+                    config,
+                }
+            })
+            .collect();
+        let mut session = Session::new("Intervals", sections, base_config.clone());
+        session.rest_s = plan.rest_s;
+        session
+    }
+
+    // a mock interview test, arithmetic then multiplication and division then a running total
+    pub fn mock_test(base_config: &GameConfiguration) -> Session {
+        let section = |name: &str, timer: i32, signs: Vec<Sign>, chained: bool| {
+            let mut config = base_config.clone();
+            config.mode = crate::config::GameMode::Timed;
+            config.timer = timer;
+            config.signs = signs;
+            config.chained = chained;
+            Section {
+                name: name.to_string(),
+                config,
+            }
+        };
+        Session::new(
+            "Mock Test",
+            vec![
+                section("Arithmetic", 120, vec![Sign::Add, Sign::Subtract], false),
+                section("Products", 180, vec![Sign::Multiply, Sign::Divide], false),
+                section("Running total", 120, Sign::ARITHMETIC.to_vec(), true),
+            ],
+            base_config.clone(),
+        )
+    }
+
+    pub fn current_section(&self) -> Option<&Section> {
+        self.sections.get(self.current)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.records.len() >= self.sections.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_plans_parse_reps_work_and_rest() {
+        let plan = IntervalPlan {
+            reps: 6,
+            work_s: 45,
+            rest_s: 15,
+        };
+        assert_eq!(IntervalPlan::parse("6x45/15"), Some(plan));
+        assert_eq!(IntervalPlan::parse(" 6 x 45 / 15 "), Some(plan));
+        assert_eq!(
+            IntervalPlan::parse("3x60/0"),
+            Some(IntervalPlan {
+                reps: 3,
+                work_s: 60,
+                rest_s: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn interval_plans_need_reps_and_work() {
+        assert_eq!(IntervalPlan::parse("0x45/15"), None);
+        assert_eq!(IntervalPlan::parse("6x0/15"), None);
+        assert_eq!(IntervalPlan::parse("6x45/-1"), None);
+        assert_eq!(IntervalPlan::parse("6x45"), None);
+        assert_eq!(IntervalPlan::parse("six"), None);
+    }
+}
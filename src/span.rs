@@ -0,0 +1,130 @@
+use chrono::{DateTime, Local};
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfiguration;
+
+// shortest span the drill will drop back to after a miss
+const MIN_SPAN: usize = 2;
+
+// one number shown and what was typed back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpanTrial {
+    pub digits: String,
+    pub answer: String,
+    // the digits were to be typed back in reverse order
+    pub reversed: bool,
+    // time from the number being hidden to the answer being submitted
+    pub duration_m: i64,
+}
+
+impl SpanTrial {
+    pub fn expected(&self) -> String {
+        if self.reversed {
+            self.digits.chars().rev().collect()
+        } else {
+            self.digits.clone()
+        }
+    }
+
+    pub fn is_correct(&self) -> bool {
+        self.answer == self.expected()
+    }
+}
+
+// a digit-span drill, the span grows by one after each success and shrinks after each miss
+#[derive(Debug, Clone)]
+pub struct DigitSpan {
+    pub trials: Vec<SpanTrial>,
+    pub digits: String,
+    pub span: usize,
+    pub reversed: bool,
+    pub shown_at: DateTime<Local>,
+    // set while a confirmation is shown, the number stays up from this instant
+    pub held_at: Option<DateTime<Local>>,
+    pub digit_ms: i64,
+    pub total_trials: usize,
+}
+
+impl DigitSpan {
+    pub fn new(config: &GameConfiguration, rng: &mut StdRng) -> DigitSpan {
+        let mut game = DigitSpan {
+            trials: vec![],
+            digits: String::new(),
+            span: (config.span_start.max(MIN_SPAN as i32)) as usize,
+            reversed: config.span_reversed,
+            shown_at: Local::now(),
+            held_at: None,
+            digit_ms: config.span_digit_ms,
+            total_trials: config.span_trials.max(1) as usize,
+        };
+        game.next_trial(rng);
+        game
+    }
+
+    fn next_trial(&mut self, rng: &mut StdRng) {
+        self.digits = (0..self.span)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+            .collect();
+        self.shown_at = Local::now();
+    }
+
+    // the number stays up for longer the more digits it has
+    fn show_ms(&self) -> i64 {
+        self.digit_ms * self.digits.len() as i64
+    }
+
+    pub fn remaining_ms(&self) -> i64 {
+        let now = self.held_at.unwrap_or(Local::now());
+        (self.show_ms() - (now - self.shown_at).num_milliseconds()).max(0)
+    }
+
+    pub fn hold(&mut self) {
+        self.held_at.get_or_insert(Local::now());
+    }
+
+    // carry on from where the number was held, so it stays up for its full time
+    pub fn release(&mut self) {
+        if let Some(held_at) = self.held_at.take() {
+            self.shown_at += Local::now() - held_at;
+        }
+    }
+
+    // true once the number has been hidden and can be typed back
+    pub fn is_answering(&self) -> bool {
+        self.remaining_ms() == 0
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.trials.len() >= self.total_trials
+    }
+
+    // record the typed digits and adapt the span for the next trial
+    pub fn submit(&mut self, input: &str, rng: &mut StdRng) {
+        let trial = SpanTrial {
+            digits: self.digits.clone(),
+            answer: input.trim().to_string(),
+            reversed: self.reversed,
+            duration_m: (Local::now() - self.shown_at).num_milliseconds() - self.show_ms(),
+        };
+        self.span = if trial.is_correct() {
+            self.span + 1
+        } else {
+            (self.span - 1).max(MIN_SPAN)
+        };
+        self.trials.push(trial);
+        if !self.is_finished() {
+            self.next_trial(rng);
+        }
+    }
+
+    // longest number recalled correctly
+    pub fn max_span(&self) -> usize {
+        self.trials
+            .iter()
+            .filter(|t| t.is_correct())
+            .map(|t| t.digits.len())
+            .max()
+            .unwrap_or(0)
+    }
+}
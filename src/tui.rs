@@ -1,5 +1,6 @@
 use crate::event::EventHandler;
 use crate::game::{AppResult, GameState, MathGame};
+use crate::mouse::ClickAreas;
 use crate::renderers::*;
 use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
@@ -51,15 +52,31 @@ impl<B: Backend> Tui<B> {
     /// [`Draw`]: ratatui::Terminal::draw
     /// [`rendering`]: crate::ui::render
     pub fn draw(&mut self, app: &mut MathGame) -> AppResult<()> {
-        match app.gamestate {
-            GameState::Setup => self.terminal.draw(|frame| render_game_splash(frame, app))?,
-            GameState::Inprogress => self.terminal.draw(|frame| app.draw(frame))?,
-            GameState::EndingSplash => self.terminal.draw(|frame| render_end_splash(frame, app))?,
-            GameState::HistorySplash => self
-                .terminal
-                .draw(|frame| crate::renderers::render_history_splash(frame, app))?,
-            GameState::SettingsSpash => todo!(),
-        };
+        // renderers record where their clickable widgets end up
+        app.click_areas = ClickAreas::default();
+        self.terminal.draw(|frame| {
+            match app.gamestate {
+                GameState::Setup => render_game_splash(frame, app),
+                GameState::Lobby => render_lobby_splash(frame, app),
+                GameState::Inprogress => app.draw(frame),
+                GameState::FlashSum => render_flash_sum(frame, app),
+                GameState::FlashResults => render_flash_results(frame, app),
+                GameState::DigitSpan => render_digit_span(frame, app),
+                GameState::SpanResults => render_span_results(frame, app),
+                GameState::Market => render_market(frame, app),
+                GameState::MarketResults => render_market_results(frame, app),
+                GameState::EndingSplash => render_end_splash(frame, app),
+                GameState::VersusSplash => render_versus_splash(frame, app),
+                GameState::Resting => render_rest_splash(frame, app),
+                GameState::SessionSplash => render_session_splash(frame, app),
+                GameState::HistorySplash => crate::renderers::render_history_splash(frame, app),
+                GameState::SettingsSpash => render_settings_splash(frame, app),
+            }
+            // a pending confirmation is drawn over whichever screen is showing
+            if app.confirm.is_some() {
+                render_confirm(frame, app);
+            }
+        })?;
         Ok(())
     }
 
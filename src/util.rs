@@ -1,11 +1,13 @@
 use std::{fmt::Display, time::Duration};
 
-use futures::io::empty;
 use rand_distr::{Distribution, Normal};
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
-use crate::game::{GameState, MathAnswer, MathGame};
+use crate::{
+    game::{MathAnswer, MathGame},
+    history::GameRecord,
+};
 
 pub const ASCII_TITLE: [&str; 5] = [
     "   ____                   _     ___                     ",
@@ -16,12 +18,79 @@ pub const ASCII_TITLE: [&str; 5] = [
 ];
 
 pub fn apply_sign(sign: &Sign, lhs: i32, rhs: i32) -> i32 {
-    return match sign {
+    match sign {
         Sign::Multiply => lhs * rhs,
         Sign::Add => lhs + rhs,
         Sign::Subtract => lhs - rhs,
         Sign::Divide => lhs / rhs,
-    };
+        Sign::Prime => is_prime(lhs) as i32,
+        // the answer to a factorisation is checked by multiplying the factors back out
+        Sign::Factorise => lhs,
+        Sign::Gcd => gcd(lhs, rhs),
+        Sign::Lcm => lhs / gcd(lhs, rhs) * rhs,
+        Sign::Mod => lhs.rem_euclid(rhs),
+    }
+}
+
+pub fn is_prime(n: i32) -> bool {
+    n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| n % d != 0)
+}
+
+pub fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// prime factors written like 2^3*3*7
+pub fn factorisation(mut n: i32) -> String {
+    let mut factors = vec![];
+    let mut p = 2;
+    while n > 1 && p * p <= n {
+        let mut power = 0;
+        while n % p == 0 {
+            n /= p;
+            power += 1;
+        }
+        match power {
+            0 => {}
+            1 => factors.push(p.to_string()),
+            _ => factors.push(format!("{}^{}", p, power)),
+        }
+        p += 1;
+    }
+    if n > 1 {
+        factors.push(n.to_string());
+    }
+    factors.join("*")
+}
+
+// multiply out a factorisation like 2^3*3*7, None unless every factor is prime
+pub fn parse_factorisation(input: &str) -> Option<i32> {
+    let mut product: i32 = 1;
+    for term in input.trim().split('*') {
+        let (base, power) = match term.split_once('^') {
+            Some((base, power)) => (base.trim().parse().ok()?, power.trim().parse().ok()?),
+            None => (term.trim().parse().ok()?, 1),
+        };
+        if !is_prime(base) {
+            return None;
+        }
+        product = product.checked_mul(base.checked_pow(power)?)?;
+    }
+    Some(product)
+}
+
+// ten points for an exact estimate, falling to none at a factor of ten out
+pub fn log_score(error_factor: f64) -> i32 {
+    (10.0 * (1.0 - error_factor.log10())).round().max(0.0) as i32
+}
+
+// closeness points over all the estimates in a game, kept apart from the score
+pub fn estimate_points(answers: &[MathAnswer]) -> i32 {
+    answers.iter().filter_map(|a| a.error_factor).map(log_score).sum()
 }
 
 pub fn match_sign(sign: &Sign) -> char {
@@ -30,6 +99,11 @@ pub fn match_sign(sign: &Sign) -> char {
         Sign::Add => '+',
         Sign::Subtract => '-',
         Sign::Divide => '/',
+        Sign::Prime => '?',
+        Sign::Factorise => '*',
+        Sign::Gcd => 'g',
+        Sign::Lcm => 'l',
+        Sign::Mod => '%',
     }
 }
 
@@ -39,6 +113,46 @@ pub enum Sign {
     Add,
     Subtract,
     Divide,
+    Prime,
+    Factorise,
+    Gcd,
+    Lcm,
+    Mod,
+}
+
+impl Sign {
+    pub const ALL: [Sign; 9] = [
+        Sign::Add,
+        Sign::Subtract,
+        Sign::Multiply,
+        Sign::Divide,
+        Sign::Prime,
+        Sign::Factorise,
+        Sign::Gcd,
+        Sign::Lcm,
+        Sign::Mod,
+    ];
+    // the four operations played by default
+    pub const ARITHMETIC: [Sign; 4] = [Sign::Add, Sign::Subtract, Sign::Multiply, Sign::Divide];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sign::Multiply => "Multiplication",
+            Sign::Add => "Addition",
+            Sign::Subtract => "Subtraction",
+            Sign::Divide => "Division",
+            Sign::Prime => "Is it prime",
+            Sign::Factorise => "Prime factorisation",
+            Sign::Gcd => "GCD",
+            Sign::Lcm => "LCM",
+            Sign::Mod => "Modulo",
+        }
+    }
+
+    // a yes/no or a list of factors is not a number a chain can carry on from
+    pub fn continues_chain(&self) -> bool {
+        !matches!(self, Sign::Prime | Sign::Factorise)
+    }
 }
 
 impl Display for Sign {
@@ -48,6 +162,11 @@ impl Display for Sign {
             Sign::Add => write!(f, "+"),
             Sign::Subtract => write!(f, "-"),
             Sign::Divide => write!(f, "/"),
+            Sign::Prime => write!(f, "prime"),
+            Sign::Factorise => write!(f, "factor"),
+            Sign::Gcd => write!(f, "gcd"),
+            Sign::Lcm => write!(f, "lcm"),
+            Sign::Mod => write!(f, "mod"),
         }
     }
 }
@@ -116,19 +235,48 @@ pub fn generate_random_durations(total_duration: Duration, count: i32) -> Vec<Du
         .collect()
 }
 
+// the history record currently highlighted in the history table
+pub fn get_selected_record(game: &MathGame) -> Option<&GameRecord> {
+    if game.game_history.history.is_empty() {
+        return None;
+    }
+    game.game_history.history.get(
+        game.history_table_state
+            .selected()
+            .unwrap_or_default()
+            .min(game.game_history.history.len() - 1),
+    )
+}
+
 pub fn get_target_answers(game: &MathGame) -> &Vec<MathAnswer> {
     static EMPTY_ANSWERS: Vec<MathAnswer> = Vec::new(); // Static empty vector
 
     match game.gamestate {
-        crate::game::GameState::HistorySplash => {
-            if game.game_history.history.is_empty() {
-                &EMPTY_ANSWERS // Create a new Vec and return a reference to it
-            } else {
-                &game.game_history.history[
-                    game.history_table_state.selected().unwrap_or_default().min(game.game_history.history.len() - 1)
-                ].answers
-            }
-            }
+        crate::game::GameState::HistorySplash => match get_selected_record(game) {
+            Some(record) => &record.answers,
+            None => &EMPTY_ANSWERS,
+        },
         _ => &game.answers,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factorisations_multiply_out_prime_powers() {
+        assert_eq!(parse_factorisation("2^2*3"), Some(12));
+        assert_eq!(parse_factorisation(" 2 * 2 * 3 "), Some(12));
+        assert_eq!(parse_factorisation("7"), Some(7));
+    }
+
+    #[test]
+    fn factorisations_reject_composites_and_bad_terms() {
+        assert_eq!(parse_factorisation("4*3"), None);
+        assert_eq!(parse_factorisation("2^"), None);
+        assert_eq!(parse_factorisation("2**3"), None);
+        assert_eq!(parse_factorisation(""), None);
+        assert_eq!(parse_factorisation("2^40"), None);
+    }
+}
@@ -0,0 +1,36 @@
+// state for a hot-seat game where two players take turns on the same question sequence
+#[derive(Debug, Clone)]
+pub struct Hotseat {
+    // index of the player currently playing, 0 or 1
+    pub player: usize,
+    // each player has their own question stream so one cannot learn the other's answers
+    pub seeds: [u64; Self::PLAYERS],
+    // indexes into the game history of each finished player's record
+    pub records: Vec<usize>,
+}
+
+impl Hotseat {
+    pub const PLAYERS: usize = 2;
+
+    pub fn new(seeds: [u64; Self::PLAYERS]) -> Hotseat {
+        Hotseat {
+            player: 0,
+            seeds,
+            records: vec![],
+        }
+    }
+
+    // seed of the questions for the player currently playing
+    pub fn seed(&self) -> u64 {
+        self.seeds[self.player]
+    }
+
+    // true when a player has finished and the next is waiting to start
+    pub fn next_player_waiting(&self) -> bool {
+        self.records.len() == self.player + 1 && self.records.len() < Self::PLAYERS
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.records.len() >= Self::PLAYERS
+    }
+}